@@ -0,0 +1,29 @@
+use std::{env, fs, io};
+
+use aoc2022::day18::Voxels;
+
+fn main() -> io::Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let format = match args.first() {
+        Some(format) => format.as_str(),
+        None => {
+            eprintln!("usage: mesh <obj|stl> [output] [--all]");
+            return Ok(());
+        }
+    };
+    let output = args
+        .get(1)
+        .filter(|arg| !arg.starts_with("--"))
+        .cloned()
+        .unwrap_or(format!("day18.{format}"));
+    let exterior_only = !args.iter().any(|arg| arg == "--all");
+
+    let input = fs::read_to_string("input/2022/day18.txt")?;
+    let voxels = Voxels::from(&input);
+    let mut file = io::BufWriter::new(fs::File::create(output)?);
+    match format {
+        "obj" => voxels.write_obj(&mut file, exterior_only),
+        "stl" => voxels.write_stl(&mut file, exterior_only),
+        _ => panic!("Unknown format: {format}"),
+    }
+}
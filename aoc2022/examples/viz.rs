@@ -0,0 +1,36 @@
+use std::{env, fs, io, path::Path, time::Duration};
+
+use aoc2022::{day14, day17};
+use aoc_common::viz::{animate, write_gif, write_pngs, Frame, Palette, Visualize};
+
+fn main() -> io::Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (day, mode) = match args.as_slice() {
+        [day, mode, ..] => (day.as_str(), mode.as_str()),
+        _ => {
+            eprintln!("usage: viz <day14|day17> <term|png|gif> [output]");
+            return Ok(());
+        }
+    };
+    let input = fs::read_to_string(format!("input/2022/{day}.txt"))?;
+
+    let frames: Vec<Frame> = match day {
+        "day14" => {
+            let mut cave = day14::Cave::new(&input, day14::Bottom::Floor);
+            cave.fill();
+            vec![cave.frame()]
+        }
+        "day17" => day17::visualize(input.trim(), 2022),
+        _ => panic!("No visualization for {day}"),
+    };
+
+    let palette = Palette::default();
+    let delay = Duration::from_millis(50);
+    let output = args.get(2).map_or(day, String::as_str);
+    match mode {
+        "term" => animate(&mut io::stdout(), &frames, &palette, delay),
+        "png" => write_pngs(&frames, Path::new(output), &palette, 4).map(|_| ()),
+        "gif" => write_gif(&frames, Path::new(output), &palette, 4, delay),
+        _ => panic!("Unknown mode: {mode}"),
+    }
+}
@@ -1,3 +1,4 @@
+use aoc_common::ocr;
 use aoc_runner_derive::aoc;
 
 #[aoc(day10, part1)]
@@ -27,8 +28,7 @@ pub fn part1(input: &str) -> i32 {
     sum
 }
 
-#[aoc(day10, part2)]
-pub fn part2(input: &str) -> String {
+pub fn render(input: &str) -> String {
     let mut x = 1;
     let mut cycle = 0;
     let mut crt = String::new();
@@ -61,6 +61,12 @@ pub fn part2(input: &str) -> String {
     crt
 }
 
+#[aoc(day10, part2)]
+pub fn part2(input: &str) -> String {
+    let crt = render(input);
+    ocr::recognize_str(&crt).unwrap_or_else(|| panic!("Unrecognized CRT image:\n{crt}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -221,7 +227,7 @@ mod tests {
     }
 
     #[test]
-    fn test_part2_sample() {
+    fn test_render_sample() {
         let expected = indoc! {"
         ##..##..##..##..##..##..##..##..##..##..
         ###...###...###...###...###...###...###.
@@ -230,6 +236,6 @@ mod tests {
         ######......######......######......####
         #######.......#######.......#######.....
     "};
-        assert_eq!(part2(SAMPLE), expected);
+        assert_eq!(render(SAMPLE), expected);
     }
 }
@@ -1,23 +1,106 @@
 use aoc_runner_derive::aoc;
-use std::collections::VecDeque;
+use std::{
+    collections::VecDeque,
+    ops::{Add, Mul},
+};
 
-#[derive(Clone)]
-enum Op {
-    Add(u64),
-    Mul(u64),
-    Square,
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+pub trait Worry: Clone + From<u64> + Add<Output = Self> + Mul<Output = Self> {
+    fn divisible_by(&self, n: u64) -> bool;
+}
+
+impl Worry for u64 {
+    fn divisible_by(&self, n: u64) -> bool {
+        self % n == 0
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl Worry for BigUint {
+    fn divisible_by(&self, n: u64) -> bool {
+        self % n == BigUint::ZERO
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Old,
+    Num(u64),
+    Add(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
 }
 
-struct Monkey {
-    items: VecDeque<u64>,
-    op: Op,
+impl Expr {
+    fn parse(s: &str) -> Expr {
+        let spaced = ['(', ')', '+', '*']
+            .iter()
+            .fold(s.to_string(), |s, &c| s.replace(c, &format!(" {c} ")));
+        let tokens = spaced.split_whitespace().collect::<Vec<_>>();
+        let mut pos = 0;
+        let expr = Expr::sum(&tokens, &mut pos);
+        if let Some(token) = tokens.get(pos) {
+            panic!("Unexpected `{token}` in `{s}`");
+        }
+        expr
+    }
+
+    fn sum(tokens: &[&str], pos: &mut usize) -> Expr {
+        let mut expr = Expr::product(tokens, pos);
+        while tokens.get(*pos) == Some(&"+") {
+            *pos += 1;
+            expr = Expr::Add(Box::new(expr), Box::new(Expr::product(tokens, pos)));
+        }
+        expr
+    }
+
+    fn product(tokens: &[&str], pos: &mut usize) -> Expr {
+        let mut expr = Expr::atom(tokens, pos);
+        while tokens.get(*pos) == Some(&"*") {
+            *pos += 1;
+            expr = Expr::Mul(Box::new(expr), Box::new(Expr::atom(tokens, pos)));
+        }
+        expr
+    }
+
+    fn atom(tokens: &[&str], pos: &mut usize) -> Expr {
+        *pos += 1;
+        match tokens.get(*pos - 1) {
+            Some(&"old") => Expr::Old,
+            Some(&"(") => {
+                let expr = Expr::sum(tokens, pos);
+                if tokens.get(*pos) != Some(&")") {
+                    panic!("Missing `)` in `{}`", tokens.join(" "));
+                }
+                *pos += 1;
+                expr
+            }
+            Some(n) => Expr::Num(n.parse().unwrap_or_else(|_| panic!("Unknown operand: {n}"))),
+            None => panic!("Unexpected end of `{}`", tokens.join(" ")),
+        }
+    }
+
+    fn eval<T: Worry>(&self, old: &T) -> T {
+        match self {
+            Expr::Old => old.clone(),
+            Expr::Num(n) => T::from(*n),
+            Expr::Add(a, b) => a.eval(old) + b.eval(old),
+            Expr::Mul(a, b) => a.eval(old) * b.eval(old),
+        }
+    }
+}
+
+struct Monkey<T> {
+    items: VecDeque<T>,
+    op: Expr,
     test_div: u64,
     if_true: usize,
     if_false: usize,
     inspections: u64,
 }
 
-fn parse(input: &str) -> Vec<Monkey> {
+fn parse<T: Worry>(input: &str) -> Vec<Monkey<T>> {
     let mut monkeys = Vec::new();
     for block in input.split("\n\n") {
         let lines: Vec<_> = block.lines().collect();
@@ -26,18 +109,9 @@ fn parse(input: &str) -> Vec<Monkey> {
             .unwrap()
             .1
             .split(", ")
-            .map(|n| n.parse().unwrap())
+            .map(|n| T::from(n.parse().unwrap()))
             .collect();
-        let op_line = lines[2].split_once("= ").unwrap().1;
-        let op = if op_line == "old * old" {
-            Op::Square
-        } else if let Some(v) = op_line.strip_prefix("old * ") {
-            Op::Mul(v.parse().unwrap())
-        } else if let Some(v) = op_line.strip_prefix("old + ") {
-            Op::Add(v.parse().unwrap())
-        } else {
-            panic!("Unknown op: {op_line}");
-        };
+        let op = Expr::parse(lines[2].split_once("= ").unwrap().1);
         let test_div = lines[3].split_whitespace().last().unwrap().parse().unwrap();
         let if_true = lines[4].split_whitespace().last().unwrap().parse().unwrap();
         let if_false = lines[5].split_whitespace().last().unwrap().parse().unwrap();
@@ -53,21 +127,13 @@ fn parse(input: &str) -> Vec<Monkey> {
     monkeys
 }
 
-#[aoc(day11, part1)]
-pub fn part1(input: &str) -> u64 {
-    let mut monkeys = parse(input);
-
-    for _round in 0..20 {
+fn play<T: Worry>(monkeys: &mut [Monkey<T>], rounds: usize, relief: impl Fn(T) -> T) {
+    for _round in 0..rounds {
         for i in 0..monkeys.len() {
-            while let Some(mut item) = monkeys[i].items.pop_front() {
+            while let Some(item) = monkeys[i].items.pop_front() {
                 monkeys[i].inspections += 1;
-                item = match monkeys[i].op {
-                    Op::Add(v) => item + v,
-                    Op::Mul(v) => item * v,
-                    Op::Square => item * item,
-                };
-                item /= 3;
-                let target = if item % monkeys[i].test_div == 0 {
+                let item = relief(monkeys[i].op.eval(&item));
+                let target = if item.divisible_by(monkeys[i].test_div) {
                     monkeys[i].if_true
                 } else {
                     monkeys[i].if_false
@@ -76,42 +142,52 @@ pub fn part1(input: &str) -> u64 {
             }
         }
     }
+}
+
+// Product of all test divisors
+pub fn modulus(input: &str) -> u64 {
+    parse::<u64>(input).iter().map(|m| m.test_div).product()
+}
+
+pub fn monkey_business(input: &str, rounds: usize, relief: impl Fn(u64) -> u64) -> u64 {
+    let mut monkeys = parse::<u64>(input);
+    play(&mut monkeys, rounds, relief);
 
     let mut inspections: Vec<_> = monkeys.iter().map(|m| m.inspections).collect();
     inspections.sort_unstable_by(|a, b| b.cmp(a));
     inspections[0] * inspections[1]
 }
 
-#[aoc(day11, part2)]
-pub fn part2(input: &str) -> u64 {
-    let mut monkeys = parse(input);
+// Replays the first rounds with exact worry levels and checks that reducing them modulo the
+// product of all divisors routes every item the same way.
+#[cfg(feature = "bigint")]
+pub fn verify_modulus(input: &str, rounds: usize) -> bool {
+    let modulo = modulus(input);
+    let mut reduced = parse::<u64>(input);
+    let mut exact = parse::<BigUint>(input);
+    play(&mut reduced, rounds, |item| item % modulo);
+    play(&mut exact, rounds, |item| item);
 
-    // Product of all test divisors
-    let modulo: u64 = monkeys.iter().map(|m| m.test_div).product();
+    reduced.iter().zip(&exact).all(|(reduced, exact)| {
+        reduced.inspections == exact.inspections
+            && reduced.items.len() == exact.items.len()
+            && reduced
+                .items
+                .iter()
+                .zip(&exact.items)
+                .all(|(&r, e)| BigUint::from(r) == e % modulo)
+    })
+}
 
-    for _round in 0..10_000 {
-        for i in 0..monkeys.len() {
-            while let Some(mut item) = monkeys[i].items.pop_front() {
-                monkeys[i].inspections += 1;
-                item = match monkeys[i].op {
-                    Op::Add(v) => item + v,
-                    Op::Mul(v) => item * v,
-                    Op::Square => item * item,
-                };
-                item %= modulo;
-                let target = if item % monkeys[i].test_div == 0 {
-                    monkeys[i].if_true
-                } else {
-                    monkeys[i].if_false
-                };
-                monkeys[target].items.push_back(item);
-            }
-        }
-    }
+#[aoc(day11, part1)]
+pub fn part1(input: &str) -> u64 {
+    monkey_business(input, 20, |item| item / 3)
+}
 
-    let mut inspections: Vec<_> = monkeys.iter().map(|m| m.inspections).collect();
-    inspections.sort_unstable_by(|a, b| b.cmp(a));
-    inspections[0] * inspections[1]
+#[aoc(day11, part2)]
+pub fn part2(input: &str) -> u64 {
+    let modulo = modulus(input);
+    monkey_business(input, 10_000, |item| item % modulo)
 }
 
 #[cfg(test)]
@@ -158,4 +234,29 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 2713310158);
     }
+
+    #[test]
+    fn test_expr() {
+        let expr = Expr::parse("(old + 1) * 2 + old * old");
+        assert_eq!(expr.eval(&3u64), 17);
+        assert_eq!(Expr::parse("old + old").eval(&4u64), 8);
+        assert_eq!(Expr::parse("3 * old").eval(&4u64), 12);
+    }
+
+    #[test]
+    fn test_commuted_operations() {
+        let input = SAMPLE
+            .replace("old * 19", "19 * old")
+            .replace("old + 6", "6 + old");
+        assert_eq!(part1(&input), 10605);
+        assert_eq!(part2(&input), 2713310158);
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_verify_modulus() {
+        assert!(verify_modulus(SAMPLE, 12));
+        let input = SAMPLE.replace("old + 3", "old + old + 3");
+        assert!(verify_modulus(&input, 12));
+    }
 }
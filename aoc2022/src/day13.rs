@@ -1,74 +1,175 @@
 use aoc_runner_derive::aoc;
-use std::cmp::Ordering;
+use std::{cmp::Ordering, fmt::Display, slice, str::FromStr};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Packet {
+#[derive(Debug, Clone)]
+pub enum Packet {
     Int(i32),
     List(Vec<Packet>),
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParsePacketError {
+    UnexpectedEnd,
+    UnexpectedChar(usize, char),
+    BadInt(usize),
+}
+
+impl Display for ParsePacketError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParsePacketError::UnexpectedEnd => write!(f, "unexpected end of packet"),
+            ParsePacketError::UnexpectedChar(pos, c) => {
+                write!(f, "unexpected `{c}` at column {}", pos + 1)
+            }
+            ParsePacketError::BadInt(pos) => write!(f, "bad integer at column {}", pos + 1),
+        }
+    }
+}
+
+impl std::error::Error for ParsePacketError {}
+
 impl Packet {
-    fn parse(s: &str) -> Packet {
-        fn parse_inner(chars: &mut std::iter::Peekable<std::str::Chars>) -> Packet {
-            let mut list = Vec::new();
-            while let Some(&c) = chars.peek() {
-                match c {
-                    '[' => {
-                        chars.next();
-                        list.push(parse_inner(chars));
-                    }
-                    ']' => {
-                        chars.next();
-                        break;
-                    }
-                    ',' => {
-                        chars.next();
-                    }
-                    d if d.is_ascii_digit() || d == '-' => {
-                        let mut num = String::new();
-                        while let Some(&d) = chars.peek() {
-                            if d.is_ascii_digit() || d == '-' {
-                                num.push(d);
-                                chars.next();
-                            } else {
-                                break;
-                            }
+    fn parse_at(s: &[u8], pos: &mut usize) -> Result<Packet, ParsePacketError> {
+        match s.get(*pos) {
+            None => Err(ParsePacketError::UnexpectedEnd),
+            Some(b'[') => {
+                *pos += 1;
+                let mut list = Vec::new();
+                if s.get(*pos) == Some(&b']') {
+                    *pos += 1;
+                    return Ok(Packet::List(list));
+                }
+                loop {
+                    list.push(Packet::parse_at(s, pos)?);
+                    match s.get(*pos) {
+                        Some(b',') => *pos += 1,
+                        Some(b']') => {
+                            *pos += 1;
+                            return Ok(Packet::List(list));
                         }
-                        list.push(Packet::Int(num.parse().unwrap()));
-                    }
-                    _ => {
-                        chars.next();
+                        Some(&c) => return Err(ParsePacketError::UnexpectedChar(*pos, c as char)),
+                        None => return Err(ParsePacketError::UnexpectedEnd),
                     }
                 }
             }
-            Packet::List(list)
+            Some(&c) if c.is_ascii_digit() || c == b'-' => {
+                let start = *pos;
+                *pos += 1;
+                while s.get(*pos).is_some_and(u8::is_ascii_digit) {
+                    *pos += 1;
+                }
+                std::str::from_utf8(&s[start..*pos])
+                    .unwrap()
+                    .parse()
+                    .map(Packet::Int)
+                    .map_err(|_| ParsePacketError::BadInt(start))
+            }
+            Some(&c) => Err(ParsePacketError::UnexpectedChar(*pos, c as char)),
+        }
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParsePacketError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().as_bytes();
+        let mut pos = 0;
+        let packet = Packet::parse_at(s, &mut pos)?;
+        match s.get(pos) {
+            Some(&c) => Err(ParsePacketError::UnexpectedChar(pos, c as char)),
+            None => Ok(packet),
         }
-        let mut chars = s.chars().peekable();
-        parse_inner(&mut chars)
     }
+}
 
-    fn cmp_packets(left: &Packet, right: &Packet) -> Ordering {
-        match (left, right) {
-            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
-            (Packet::List(a), Packet::List(b)) => {
-                for (x, y) in a.iter().zip(b.iter()) {
-                    let ord = Packet::cmp_packets(x, y);
-                    if ord != Ordering::Equal {
-                        return ord;
+impl Display for Packet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Packet::Int(n) => write!(f, "{n}"),
+            Packet::List(list) => {
+                write!(f, "[")?;
+                for (i, packet) in list.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
                     }
+                    write!(f, "{packet}")?;
                 }
-                a.len().cmp(&b.len())
-            }
-            (Packet::Int(_), Packet::List(_)) => {
-                Packet::cmp_packets(&Packet::List(vec![left.clone()]), right)
-            }
-            (Packet::List(_), Packet::Int(_)) => {
-                Packet::cmp_packets(left, &Packet::List(vec![right.clone()]))
+                write!(f, "]")
             }
         }
     }
 }
 
+fn cmp_lists(a: &[Packet], b: &[Packet]) -> Ordering {
+    for (x, y) in a.iter().zip(b.iter()) {
+        let ord = x.cmp(y);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+// An integer compared against a list acts as a one-element list; borrowing it as a slice avoids
+// building that list.
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            (Packet::List(a), Packet::List(b)) => cmp_lists(a, b),
+            (Packet::Int(_), Packet::List(b)) => cmp_lists(slice::from_ref(self), b),
+            (Packet::List(a), Packet::Int(_)) => cmp_lists(a, slice::from_ref(other)),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Equality follows the packet order, so `[[2]]` equals `[2]` and `2`.
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+#[cfg(feature = "json")]
+impl From<&Packet> for serde_json::Value {
+    fn from(packet: &Packet) -> Self {
+        match packet {
+            Packet::Int(n) => serde_json::Value::from(*n),
+            Packet::List(list) => list.iter().map(serde_json::Value::from).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl TryFrom<&serde_json::Value> for Packet {
+    type Error = serde_json::Value;
+
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::Array(list) => list
+                .iter()
+                .map(Packet::try_from)
+                .collect::<Result<_, _>>()
+                .map(Packet::List),
+            serde_json::Value::Number(n) => n
+                .as_i64()
+                .and_then(|n| i32::try_from(n).ok())
+                .map(Packet::Int)
+                .ok_or_else(|| value.clone()),
+            _ => Err(value.clone()),
+        }
+    }
+}
+
 #[aoc(day13, part1)]
 pub fn part1(input: &str) -> usize {
     input
@@ -76,34 +177,27 @@ pub fn part1(input: &str) -> usize {
         .enumerate()
         .filter_map(|(i, pair)| {
             let mut lines = pair.lines();
-            let left = Packet::parse(lines.next().unwrap());
-            let right = Packet::parse(lines.next().unwrap());
-            if Packet::cmp_packets(&left, &right) == Ordering::Less {
-                Some(i + 1)
-            } else {
-                None
-            }
+            let left: Packet = lines.next().unwrap().parse().unwrap();
+            let right: Packet = lines.next().unwrap().parse().unwrap();
+            (left < right).then_some(i + 1)
         })
         .sum()
 }
 
 #[aoc(day13, part2)]
 pub fn part2(input: &str) -> usize {
-    let mut packets: Vec<Packet> = input
+    let packets: Vec<Packet> = input
         .lines()
         .filter(|l| !l.trim().is_empty())
-        .map(Packet::parse)
+        .map(|l| l.parse().unwrap())
         .collect();
 
-    let divider1 = Packet::parse("[[2]]");
-    let divider2 = Packet::parse("[[6]]");
-    packets.push(divider1.clone());
-    packets.push(divider2.clone());
+    let divider1: Packet = "[[2]]".parse().unwrap();
+    let divider2: Packet = "[[6]]".parse().unwrap();
 
-    packets.sort_by(|a, b| Packet::cmp_packets(a, b));
-
-    let idx1 = packets.iter().position(|p| *p == divider1).unwrap() + 1;
-    let idx2 = packets.iter().position(|p| *p == divider2).unwrap() + 1;
+    // Sorted positions are just the number of packets ordered before each divider
+    let idx1 = packets.iter().filter(|&p| *p < divider1).count() + 1;
+    let idx2 = packets.iter().filter(|&p| *p < divider2).count() + 2;
 
     idx1 * idx2
 }
@@ -148,4 +242,58 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 140);
     }
+
+    #[test]
+    fn test_round_trip() {
+        for line in SAMPLE.lines().filter(|l| !l.is_empty()) {
+            assert_eq!(line.parse::<Packet>().unwrap().to_string(), line);
+        }
+        let mut packets = SAMPLE
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(|l| l.parse::<Packet>().unwrap())
+            .collect::<Vec<_>>();
+        packets.sort();
+        assert_eq!(packets[0].to_string(), "[]");
+        assert_eq!(packets[packets.len() - 1].to_string(), "[9]");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        use ParsePacketError::*;
+        assert_eq!("[1,2".parse::<Packet>(), Err(UnexpectedEnd));
+        assert_eq!("[1,2]]".parse::<Packet>(), Err(UnexpectedChar(5, ']')));
+        assert_eq!("[1,,2]".parse::<Packet>(), Err(UnexpectedChar(3, ',')));
+        assert_eq!("[1 2]".parse::<Packet>(), Err(UnexpectedChar(2, ' ')));
+        assert_eq!("[-]".parse::<Packet>(), Err(BadInt(1)));
+        assert_eq!(
+            "[a]".parse::<Packet>().unwrap_err().to_string(),
+            "unexpected `a` at column 2"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json() {
+        let packet: Packet = "[1,[2,[3,[4,[5,6,7]]]],8,9]".parse().unwrap();
+        let value = serde_json::Value::from(&packet);
+        assert_eq!(
+            value,
+            serde_json::json!([1, [2, [3, [4, [5, 6, 7]]]], 8, 9])
+        );
+        assert_eq!(
+            Packet::try_from(&value).unwrap().to_string(),
+            packet.to_string()
+        );
+        assert!(Packet::try_from(&serde_json::json!([1, "2"])).is_err());
+    }
+
+    #[test]
+    fn test_error_into_box() {
+        let parse = |s: &str| -> Result<Packet, Box<dyn std::error::Error>> { Ok(s.parse()?) };
+        assert_eq!(
+            parse("[1,").unwrap_err().to_string(),
+            "unexpected end of packet"
+        );
+    }
 }
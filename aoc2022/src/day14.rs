@@ -1,105 +1,182 @@
+use aoc_common::viz::{Frame, Visualize};
 use aoc_runner_derive::aoc;
 
-fn parse(input: &str) -> (Vec<Vec<bool>>, usize) {
-    let (max_x, max_y) = input
+const SOURCE: (i64, usize) = (500, 0);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Air,
+    Rock,
+    Sand,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bottom {
+    Abyss,
+    Floor,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cave {
+    tiles: Vec<Tile>,
+    min_x: i64,
+    width: usize,
+    max_y: usize,
+    bottom: Bottom,
+    // The fall path of the previous grain; the next one follows it down to the last open cell
+    path: Vec<(i64, usize)>,
+    settled: usize,
+}
+
+fn parse(input: &str) -> Vec<Vec<(i64, usize)>> {
+    input
         .lines()
-        .flat_map(|line| {
-            line.split(" -> ").map(|p| {
-                let (x, y) = p.split_once(',').unwrap();
-                (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap())
-            })
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            line.split(" -> ")
+                .map(|p| {
+                    let (x, y) = p.split_once(',').unwrap();
+                    (x.parse().unwrap(), y.parse().unwrap())
+                })
+                .collect()
         })
-        .fold((0, 0), |(max_x, max_y), (x, y)| {
-            (max_x.max(x), max_y.max(y))
-        });
+        .collect()
+}
 
-    // Add a large enough buffer for sand to spread
-    let buffer = max_y + 3; // +3 for safety
-    let width = max_x + buffer * 2;
-    let mut blocked = vec![vec![false; width]; max_y + 1];
-    for line in input.lines() {
-        let points: Vec<(usize, usize)> = line
-            .split(" -> ")
-            .map(|p| {
-                let (x, y) = p.split_once(',').unwrap();
-                (x.parse().unwrap(), y.parse().unwrap())
-            })
-            .collect();
-        for w in points.windows(2) {
-            let (x1, y1) = w[0];
-            let (x2, y2) = w[1];
-            if x1 == x2 {
+impl Cave {
+    pub fn new(input: &str, bottom: Bottom) -> Self {
+        let paths = parse(input);
+        let points = paths.iter().flatten();
+        let max_y = points.clone().map(|&(_, y)| y).max().unwrap();
+
+        // A grain moves at most one column per row it falls, and the floor sits two rows below
+        // the lowest rock, so no grain can ever leave this range. It may reach past column 0.
+        let floor = max_y + 2;
+        let min_x = points
+            .clone()
+            .map(|&(x, _)| x)
+            .min()
+            .unwrap()
+            .min(SOURCE.0 - floor as i64);
+        let max_x = points
+            .map(|&(x, _)| x)
+            .max()
+            .unwrap()
+            .max(SOURCE.0 + floor as i64);
+        let width = (max_x - min_x + 1) as usize;
+
+        let mut cave = Cave {
+            tiles: vec![Tile::Air; width * floor],
+            min_x,
+            width,
+            max_y,
+            bottom,
+            path: vec![SOURCE],
+            settled: 0,
+        };
+        for path in paths {
+            for w in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (w[0], w[1]);
                 for y in y1.min(y2)..=y1.max(y2) {
-                    blocked[y][x1 + buffer] = true;
-                }
-            } else {
-                for x in x1.min(x2)..=x1.max(x2) {
-                    blocked[y1][x + buffer] = true;
+                    for x in x1.min(x2)..=x1.max(x2) {
+                        cave.set(x, y, Tile::Rock);
+                    }
                 }
             }
         }
+        cave
     }
-    (blocked, buffer)
-}
 
-#[aoc(day14, part1)]
-pub fn part1(input: &str) -> usize {
-    let (mut blocked, buffer) = parse(input);
-    let max_y = blocked.len() - 1;
+    fn column(&self, x: i64) -> Option<usize> {
+        usize::try_from(x - self.min_x)
+            .ok()
+            .filter(|&i| i < self.width)
+    }
+
+    pub fn tile(&self, x: i64, y: usize) -> Tile {
+        if self.bottom == Bottom::Floor && y == self.max_y + 2 {
+            return Tile::Rock;
+        }
+        match self.column(x) {
+            Some(i) if y <= self.max_y + 2 => self.tiles[y * self.width + i],
+            _ => Tile::Air,
+        }
+    }
+
+    fn set(&mut self, x: i64, y: usize, tile: Tile) {
+        let i = self.column(x).unwrap();
+        self.tiles[y * self.width + i] = tile;
+    }
+
+    pub fn settled(&self) -> usize {
+        self.settled
+    }
 
-    let mut answer = 0;
-    loop {
-        let (mut x, mut y) = (500 + buffer, 0);
+    // Drops one grain and returns where it came to rest, or None once sand falls into the abyss
+    // or the source is blocked.
+    pub fn drop_grain(&mut self) -> Option<(i64, usize)> {
         loop {
-            if y == max_y {
-                return answer;
+            let &(x, y) = self.path.last()?;
+            if self.bottom == Bottom::Abyss && y == self.max_y {
+                return None;
             }
-            if !blocked[y + 1][x] {
-                y += 1;
-            } else if !blocked[y + 1][x - 1] {
-                x -= 1;
-                y += 1;
-            } else if !blocked[y + 1][x + 1] {
-                x += 1;
-                y += 1;
-            } else {
-                blocked[y][x] = true;
-                answer += 1;
-                break;
+
+            let next = [x, x - 1, x + 1]
+                .into_iter()
+                .find(|&x| self.tile(x, y + 1) == Tile::Air);
+            match next {
+                // Past the outermost rock there is nothing left to land on but the abyss
+                Some(x) if self.bottom == Bottom::Abyss && self.column(x).is_none() => return None,
+                Some(x) => self.path.push((x, y + 1)),
+                None => {
+                    self.set(x, y, Tile::Sand);
+                    self.settled += 1;
+                    self.path.pop();
+                    return Some((x, y));
+                }
             }
         }
     }
+
+    pub fn fill(&mut self) -> usize {
+        while self.drop_grain().is_some() {}
+        self.settled
+    }
+}
+
+impl Visualize for Cave {
+    fn frame(&self) -> Frame {
+        let used = (0..self.tiles.len())
+            .filter(|&i| self.tiles[i] != Tile::Air)
+            .map(|i| ((i % self.width) as i64 + self.min_x, i / self.width))
+            .chain([SOURCE]);
+        let (min_x, max_x, max_y) = used.fold((i64::MAX, 0, 0), |(min_x, max_x, max_y), (x, y)| {
+            (min_x.min(x), max_x.max(x), max_y.max(y))
+        });
+        let max_y = match self.bottom {
+            Bottom::Abyss => max_y,
+            Bottom::Floor => self.max_y + 2,
+        };
+
+        Frame::from_rows((0..=max_y).map(|y| {
+            (min_x..=max_x).map(move |x| match self.tile(x, y) {
+                Tile::Rock => '#',
+                Tile::Sand => 'o',
+                Tile::Air if (x, y) == SOURCE => '+',
+                Tile::Air => '.',
+            })
+        }))
+    }
+}
+
+#[aoc(day14, part1)]
+pub fn part1(input: &str) -> usize {
+    Cave::new(input, Bottom::Abyss).fill()
 }
 
 #[aoc(day14, part2)]
 pub fn part2(input: &str) -> usize {
-    let (mut blocked, buffer) = parse(input);
-    let width = blocked[0].len();
-    blocked.push(vec![false; width]);
-    blocked.push(vec![true; width]);
-
-    let mut answer = 0;
-    loop {
-        let (mut x, mut y) = (500 + buffer, 0);
-        loop {
-            if !blocked[y + 1][x] {
-                y += 1;
-            } else if !blocked[y + 1][x - 1] {
-                x -= 1;
-                y += 1;
-            } else if !blocked[y + 1][x + 1] {
-                x += 1;
-                y += 1;
-            } else {
-                blocked[y][x] = true;
-                answer += 1;
-                if x == 500 + buffer && y == 0 {
-                    return answer;
-                }
-                break;
-            }
-        }
-    }
+    Cave::new(input, Bottom::Floor).fill()
 }
 
 #[cfg(test)]
@@ -120,5 +197,111 @@ mod tests {
     #[test]
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 93);
+        assert_eq!(simulate(SAMPLE, Bottom::Floor), 93);
+    }
+
+    #[test]
+    fn test_left_edge() {
+        // A one-cell channel of rocks leads every grain down and left past column 0
+        let input = (0..=500)
+            .map(|k| format!("{},{} -> {},{1}", 500 - k, k + 1, 501 - k))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Cave::new(&input, Bottom::Abyss).fill(), 0);
+        let mut cave = Cave::new(&input, Bottom::Floor);
+        assert_eq!(cave.drop_grain(), Some((-1, 502)));
+        assert_eq!(cave.fill(), 505);
+        assert_eq!(simulate(&input, Bottom::Floor), 505);
+    }
+
+    // Drops every grain from the source one cell at a time
+    fn simulate(input: &str, bottom: Bottom) -> usize {
+        let paths = parse(input);
+        let max_y = paths.iter().flatten().map(|&(_, y)| y).max().unwrap();
+        let floor = max_y + 2;
+        let xs = paths.iter().flatten().map(|&(x, _)| x);
+        let min_x = xs.clone().min().unwrap().min(SOURCE.0 - floor as i64);
+        let width = (xs.max().unwrap().max(SOURCE.0 + floor as i64) - min_x + 1) as usize;
+        let index = |x: i64, y: usize| y * width + (x - min_x) as usize;
+        let mut blocked = vec![false; width * floor];
+        for path in paths {
+            for w in path.windows(2) {
+                let ((x1, y1), (x2, y2)) = (w[0], w[1]);
+                for y in y1.min(y2)..=y1.max(y2) {
+                    for x in x1.min(x2)..=x1.max(x2) {
+                        blocked[index(x, y)] = true;
+                    }
+                }
+            }
+        }
+        let mut settled = 0;
+        while !blocked[index(SOURCE.0, SOURCE.1)] {
+            let (mut x, mut y) = SOURCE;
+            while let Some(next) = [x, x - 1, x + 1]
+                .into_iter()
+                .find(|&x| y + 1 < floor && !blocked[index(x, y + 1)])
+            {
+                (x, y) = (next, y + 1);
+            }
+            if bottom == Bottom::Abyss && y > max_y {
+                break;
+            }
+            blocked[index(x, y)] = true;
+            settled += 1;
+        }
+        settled
+    }
+
+    #[test]
+    fn test_against_simulation() {
+        let mut rng = aoc_common::rng::Rng::new(14);
+        for size in [1, 5, 20] {
+            let input = crate::gen::day14(&mut rng, size);
+            assert_eq!(part1(&input), simulate(&input, Bottom::Abyss), "{input}");
+            assert_eq!(part2(&input), simulate(&input, Bottom::Floor), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_settled_grid() {
+        let mut cave = Cave::new(SAMPLE, Bottom::Abyss);
+        assert_eq!(cave.drop_grain(), Some((500, 8)));
+        cave.fill();
+        assert_eq!(cave.settled(), 24);
+        assert_eq!(
+            cave.frame().to_string(),
+            indoc! {"
+            ......+...
+            ..........
+            ......o...
+            .....ooo..
+            ....#ooo##
+            ...o#ooo#.
+            ..###ooo#.
+            ....oooo#.
+            .o.ooooo#.
+            #########.
+        "}
+        );
+
+        let mut cave = Cave::new(SAMPLE, Bottom::Floor);
+        cave.fill();
+        assert_eq!(
+            cave.frame().to_string(),
+            indoc! {"
+            ..........o..........
+            .........ooo.........
+            ........ooooo........
+            .......ooooooo.......
+            ......oo#ooo##o......
+            .....ooo#ooo#ooo.....
+            ....oo###ooo#oooo....
+            ...oooo.oooo#ooooo...
+            ..oooooooooo#oooooo..
+            .ooo#########ooooooo.
+            ooooo.......ooooooooo
+            #####################
+        "}
+        );
     }
 }
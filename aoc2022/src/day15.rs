@@ -1,7 +1,38 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::RangeInclusive};
 
+use aoc_common::params::Overrides;
 use aoc_runner_derive::aoc;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub row: i64,
+    pub max_coord: i64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            row: 2_000_000,
+            max_coord: 4_000_000,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params {
+        row: 10,
+        max_coord: 20,
+    };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2022, "day15", "row", &mut params.row);
+        overrides.apply(2022, "day15", "max_coord", &mut params.max_coord);
+        params
+    }
+}
+
 fn parse(input: &str) -> Vec<((i64, i64), (i64, i64))> {
     input
         .lines()
@@ -20,105 +51,255 @@ fn parse(input: &str) -> Vec<((i64, i64), (i64, i64))> {
         .collect()
 }
 
-#[aoc(day15, part1)]
-pub fn part1(input: &str) -> usize {
-    let sensors = parse(input);
-    let target_y = 2_000_000;
+// A sensor's diamond |x - sx| + |y - sy| <= d is the square [sx + sy - d, sx + sy + d] x
+// [sx - sy - d, sx - sy + d] once rotated to (u, v) = (x + y, x - y), so coverage becomes a
+// union of axis-aligned squares and the uncovered part of any area is a set of rectangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub u0: i64,
+    pub u1: i64,
+    pub v0: i64,
+    pub v1: i64,
+}
 
-    let mut cannot_be = vec![];
-    let mut beacons_on_row = HashSet::new();
+impl Rect {
+    fn overlaps(&self, other: &Rect) -> bool {
+        self.u0 <= other.u1 && other.u0 <= self.u1 && self.v0 <= other.v1 && other.v0 <= self.v1
+    }
 
-    for &((sx, sy), (bx, by)) in &sensors {
-        let dist = (sx - bx).abs() + (sy - by).abs();
-        let dy = (sy - target_y).abs();
-        if dy > dist {
-            continue;
+    // The pieces of `self` left after cutting out `other`
+    fn subtract(&self, other: &Rect) -> Vec<Rect> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+        let mut pieces = vec![];
+        if self.u0 < other.u0 {
+            pieces.push(Rect {
+                u1: other.u0 - 1,
+                ..*self
+            });
+        }
+        if other.u1 < self.u1 {
+            pieces.push(Rect {
+                u0: other.u1 + 1,
+                ..*self
+            });
         }
-        let dx = dist - dy;
-        cannot_be.push((sx - dx, sx + dx));
-        if by == target_y {
-            beacons_on_row.insert(bx);
+        let (u0, u1) = (self.u0.max(other.u0), self.u1.min(other.u1));
+        if self.v0 < other.v0 {
+            pieces.push(Rect {
+                u0,
+                u1,
+                v1: other.v0 - 1,
+                ..*self
+            });
         }
+        if other.v1 < self.v1 {
+            pieces.push(Rect {
+                u0,
+                u1,
+                v0: other.v1 + 1,
+                ..*self
+            });
+        }
+        pieces
     }
 
-    cannot_be.sort_unstable();
-    let mut answer = 0;
-    let mut max = i64::MIN;
-    for &(start, end) in &cannot_be {
-        if start > max {
-            answer += end - start + 1;
-        } else if end > max {
-            answer += end - max;
-        }
-        max = max.max(end);
+    // Columns of the (x, y) area that hold at least one cell of this rectangle: each bound
+    // comes from pairing a lower limit on y with an upper one.
+    fn columns(&self, xs: &RangeInclusive<i64>, ys: &RangeInclusive<i64>) -> RangeInclusive<i64> {
+        let (y0, y1) = (*ys.start(), *ys.end());
+        let start = [
+            *xs.start(),
+            y0 + self.v0,
+            self.u0 - y1,
+            (self.u0 + self.v0 + 1).div_euclid(2),
+        ];
+        let end = [
+            *xs.end(),
+            self.u1 - y0,
+            y1 + self.v1,
+            (self.u1 + self.v1).div_euclid(2),
+        ];
+        start.into_iter().max().unwrap()..=end.into_iter().min().unwrap()
+    }
+
+    pub fn cells(
+        &self,
+        xs: RangeInclusive<i64>,
+        ys: RangeInclusive<i64>,
+    ) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.columns(&xs, &ys).flat_map(move |x| {
+            let y0 = (*ys.start()).max(self.u0 - x).max(x - self.v1);
+            let y1 = (*ys.end()).min(self.u1 - x).min(x - self.v0);
+            (y0..=y1).map(move |y| (x, y))
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    squares: Vec<Rect>,
+    beacons: HashSet<(i64, i64)>,
+}
+
+impl Coverage {
+    pub fn from(input: &str) -> Self {
+        let sensors = parse(input);
+        let squares = sensors
+            .iter()
+            .map(|&((sx, sy), (bx, by))| {
+                let d = (sx - bx).abs() + (sy - by).abs();
+                Rect {
+                    u0: sx + sy - d,
+                    u1: sx + sy + d,
+                    v0: sx - sy - d,
+                    v1: sx - sy + d,
+                }
+            })
+            .collect();
+        let beacons = sensors.iter().map(|&(_, beacon)| beacon).collect();
+        Coverage { squares, beacons }
     }
 
-    for &bx in &beacons_on_row {
-        for &(start, end) in &cannot_be {
-            if bx >= start && bx <= end {
-                answer -= 1;
-                break;
+    // Merged ranges of x covered by at least one sensor on row y
+    pub fn row(&self, y: i64) -> Vec<RangeInclusive<i64>> {
+        let mut ranges = self
+            .squares
+            .iter()
+            .map(|s| (s.u0 - y).max(s.v0 + y)..=(s.u1 - y).min(s.v1 + y))
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| *range.start());
+
+        let mut merged: Vec<RangeInclusive<i64>> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if *range.start() <= last.end() + 1 => {
+                    *last = *last.start()..=*last.end().max(range.end());
+                }
+                _ => merged.push(range),
             }
         }
+        merged
+    }
+
+    pub fn uncovered_regions(&self, xs: RangeInclusive<i64>, ys: RangeInclusive<i64>) -> Vec<Rect> {
+        let bounds = Rect {
+            u0: xs.start() + ys.start(),
+            u1: xs.end() + ys.end(),
+            v0: xs.start() - ys.end(),
+            v1: xs.end() - ys.start(),
+        };
+        self.squares
+            .iter()
+            .fold(vec![bounds], |regions, square| {
+                regions.iter().flat_map(|r| r.subtract(square)).collect()
+            })
+            .into_iter()
+            .filter(|region| !region.columns(&xs, &ys).is_empty())
+            .collect()
+    }
+
+    pub fn uncovered(&self, xs: RangeInclusive<i64>, ys: RangeInclusive<i64>) -> Vec<(i64, i64)> {
+        self.uncovered_regions(xs.clone(), ys.clone())
+            .iter()
+            .flat_map(|region| region.cells(xs.clone(), ys.clone()).collect::<Vec<_>>())
+            .collect()
     }
-    answer as usize
 }
 
-fn dist(p1: (i64, i64), p2: (i64, i64)) -> i64 {
-    (p1.0 - p2.0).abs() + (p1.1 - p2.1).abs()
+pub fn part1_with(input: &str, params: &Params) -> usize {
+    let coverage = Coverage::from(input);
+    let covered = coverage.row(params.row);
+    let beacons = coverage
+        .beacons
+        .iter()
+        .filter(|&&(bx, by)| by == params.row && covered.iter().any(|r| r.contains(&bx)))
+        .count();
+    covered
+        .iter()
+        .map(|r| (r.end() - r.start() + 1) as usize)
+        .sum::<usize>()
+        - beacons
 }
 
-fn ipts(s1x: i64, s1y: i64, d1: i64, s2x: i64, s2y: i64, d2: i64) -> Vec<(i64, i64)> {
-    let pts = [
-        (s1x - d1, s2x - d2),
-        (s1x - d1, s2x + d2),
-        (s1x + d1, s2x - d2),
-        (s1x + d1, s2x + d2),
-    ];
-    pts.iter()
-        .map(|&(x1, x2)| ((x2 + s2y + x1 - s1y) / 2, (x2 + s2y - x1 + s1y) / 2))
-        .collect()
+#[aoc(day15, part1)]
+pub fn part1(input: &str) -> usize {
+    part1_with(input, &Params::from_env())
 }
 
-fn intersect(s1: (i64, i64), d1: i64, s2: (i64, i64), d2: i64) -> Vec<(i64, i64)> {
-    let mut v = ipts(s1.0, s1.1, d1, s2.0, s2.1, d2);
-    v.extend(ipts(s2.0, s2.1, d2, s1.0, s1.1, d1));
-    v
+pub fn part2_with(input: &str, params: &Params) -> i64 {
+    let coverage = Coverage::from(input);
+    let area = 0..=params.max_coord;
+    let cells = coverage.uncovered(area.clone(), area);
+    match cells.as_slice() {
+        [(x, y)] => x * 4_000_000 + y,
+        _ => panic!("Expected one uncovered cell, found {}", cells.len()),
+    }
 }
 
 #[aoc(day15, part2)]
 pub fn part2(input: &str) -> i64 {
-    let sensors: Vec<((i64, i64), i64)> = input
-        .lines()
-        .map(|line| {
-            let nums: Vec<i64> = line
-                .split(|c: char| !c.is_ascii_digit() && c != '-')
-                .filter(|s| !s.is_empty())
-                .map(|s| s.parse().unwrap())
-                .collect();
-            let s = (nums[0], nums[1]);
-            let b = (nums[2], nums[3]);
-            let d = dist(s, b);
-            (s, d)
+    part2_with(input, &Params::from_env())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const SAMPLE: &str = indoc! {"
+        Sensor at x=2, y=18: closest beacon is at x=-2, y=15
+        Sensor at x=9, y=16: closest beacon is at x=10, y=16
+        Sensor at x=13, y=2: closest beacon is at x=15, y=3
+        Sensor at x=12, y=14: closest beacon is at x=10, y=16
+        Sensor at x=10, y=20: closest beacon is at x=10, y=16
+        Sensor at x=14, y=17: closest beacon is at x=10, y=16
+        Sensor at x=8, y=7: closest beacon is at x=2, y=10
+        Sensor at x=2, y=0: closest beacon is at x=2, y=10
+        Sensor at x=0, y=11: closest beacon is at x=2, y=10
+        Sensor at x=20, y=14: closest beacon is at x=25, y=17
+        Sensor at x=17, y=20: closest beacon is at x=21, y=22
+        Sensor at x=16, y=7: closest beacon is at x=15, y=3
+        Sensor at x=14, y=3: closest beacon is at x=15, y=3
+        Sensor at x=20, y=1: closest beacon is at x=15, y=3
+    "};
+
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1_with(SAMPLE, &Params::SAMPLE), 26);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), 56000011);
+    }
+
+    fn brute_force_covered(sensors: &[((i64, i64), (i64, i64))], (x, y): (i64, i64)) -> bool {
+        sensors.iter().any(|&((sx, sy), (bx, by))| {
+            (sx - x).abs() + (sy - y).abs() <= (sx - bx).abs() + (sy - by).abs()
         })
-        .collect();
+    }
 
-    let max_coord = 4_000_000;
+    #[test]
+    fn test_coverage() {
+        let coverage = Coverage::from(SAMPLE);
+        assert_eq!(coverage.row(10), [-2..=24]);
+        assert_eq!(coverage.row(11), [-3..=13, 15..=25]);
+        assert_eq!(coverage.uncovered(0..=20, 0..=20), [(14, 11)]);
 
-    for (i, &(s1, d1)) in sensors.iter().enumerate() {
-        for (j, &(s2, d2)) in sensors.iter().enumerate() {
-            if i == j {
-                continue;
-            }
-            for (x, y) in intersect(s1, d1 + 1, s2, d2 + 1) {
-                if x < 0 || x > max_coord || y < 0 || y > max_coord {
-                    continue;
-                }
-                if sensors.iter().all(|&(s, d)| dist((x, y), s) > d) {
-                    return x * 4_000_000 + y;
+        let sensors = parse(SAMPLE);
+        let mut expected = vec![];
+        for x in -8..=30 {
+            for y in -5..=27 {
+                if !brute_force_covered(&sensors, (x, y)) {
+                    expected.push((x, y));
                 }
             }
         }
+        let mut cells = coverage.uncovered(-8..=30, -5..=27);
+        cells.sort_unstable();
+        assert_eq!(cells, expected);
     }
-    panic!("No solution found");
 }
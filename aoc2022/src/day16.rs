@@ -1,7 +1,15 @@
-use std::{collections::HashMap, str::FromStr};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, VecDeque},
+    str::FromStr,
+};
 
+use aoc_common::bitset::BitSet;
 use aoc_runner_derive::aoc;
 
+// Up to this many useful valves the sets fit a dense table indexed by mask
+const DENSE_VALVES: usize = 16;
+
 #[derive(Debug, Clone)]
 struct Valve {
     name: String,
@@ -92,208 +100,215 @@ fn parse(input: &str) -> Vec<SimpleValve> {
 }
 
 /*
- * Convert the valves into a weighted graph and generate a min distance matrix by using floyd warshall
- *
- *
- * How we keep track of open valves
- * Let's say that we have a list of 8 valves, and the starting valve is at index 3
- * The starting bitmask in this case would look like this
- * 0b11110111
- *
- * Open valves are represented by 0s and closed ones are 1s
- *
- * the size of the bitmask is based on how many valves we want to keep track of
- * in our case the size is 2^8 - 1
- * 2^8     = 0b100000000
- * 2^8 - 1 = 0b011111111
- *
- * As perform the simulation we modify the bitmask according to what valves are opened, and we
- * keep track of the highest possible flow rate out of each path combination
- * We also use a hashmap that keeps track the highest flow rate that we can get out of
- * different valve combinations (This will be important for part2)
- *
- * # Part 1
+ * Only valves with a non-zero flow rate are worth walking to, so they get a compressed index
+ * 0..n (plus one extra slot for the starting valve) and a distance matrix between them, built
+ * with a BFS from each of those valves over the full tunnel graph.
  *
- * This part is pretty straightforward, we can just use the highest flow rate returned by the
- * simulation
+ * A single actor is simulated with a DFS that records, for every set of opened valves, the
+ * best total pressure it can release by opening exactly that set.
  *
+ * With few useful valves the sets are masks into a table over every subset. Taking the best
+ * score over all submasks of every mask turns that into "the best pressure using only valves
+ * from this mask". Each extra actor then picks one of its own sets and leaves the complement
+ * to the others, so no pairs of sets have to be checked for overlap.
  *
- * # Part 2
+ * With more useful valves the sets are bitsets keyed in a map holding only the sets an actor
+ * can actually open in time. The actors are merged one at a time over the unions of disjoint
+ * sets, and the last actor takes the best set that is disjoint from the others' union.
  *
- * Here, we simulate the paths of the elf and the elpehant separately which results in 2 path
- * hashmaps
- *
- * After that we loop over each of the elf's paths and find paths that the elephant took
- * that don't overlap (using some bitmasks magic) with the elf path being checked;
- * Overlap here means that both of them opened the same valves, so we basically discard all
- * paths where both of them have at least 1 valve in common because a valve can only be opened
- * once.
- *
- * (! elf_mask) & (! elephant_mask) & init_mask == 0
- * The reason why we flip elf_mask's and elephant_mask's bits is because open valves are represented by 0s
- * instead of 1s (not sure why I thought this was a good idea) and then we have to AND the result
- * with the initial mask because when we flip the bits of a number, all of the bits are flipped (depending on the integer size being used)
- * and not only the ones being used, for example if we have 0b0011 that is stored within a u16 and
- * we flip the bits we'll end up with 0b1111111111111100, as you can see the flipping will happen
- * across all 16 bits, and that's why we AND the result with the initial_mask.
- *
- * If the result of the above bitmask expression results in a number that's bigger than 0 that
- * means that we have overlaps and we have to skip the current pair.
- * For pairs that don't have overlaps we add their highest flow rates together and compare them
- * with the rest.
+ * Either way any actor may also stay idle (the empty set), so fewer actors than k can do the
+ * work.
  */
-fn simulate(
-    valves: &Vec<SimpleValve>,
-    dist: &Vec<Vec<u32>>,
-    init_mask: u64,
-    start_idx: usize,
-    minutes: u32,
-) -> (u32, HashMap<u64, u32>) {
-    let non_zero_valves: Vec<usize> = valves
-        .iter()
-        .enumerate()
-        .filter(|(_, x)| x.rate > 0)
-        .map(|(i, _)| i)
-        .collect();
-
-    let flow = 0;
-    let mut mask_flow: HashMap<u64, u32> = HashMap::new();
-
-    let flow = traveling_salesman(
-        valves,
-        &mut mask_flow,
-        &non_zero_valves,
-        &dist,
-        init_mask,
-        minutes,
-        flow,
-        start_idx,
-        0,
-    );
-
-    (flow, mask_flow)
+struct Network {
+    rates: Vec<u32>,
+    dist: Vec<Vec<u32>>,
 }
 
-fn traveling_salesman(
-    valves: &Vec<SimpleValve>,
-    memo: &mut HashMap<u64, u32>,
-    non_zero_valves: &Vec<usize>,
-    dist: &Vec<Vec<u32>>,
-    mask: u64,
-    minutes: u32,
-    flow: u32,
-    i: usize,
-    depth: u32,
-) -> u32 {
-    let mut max_flow = flow;
-
-    memo.insert(mask, *memo.get(&mask).unwrap_or(&0).max(&flow));
-
-    for &j in non_zero_valves.iter() {
-        let cur_minutes = minutes
-            .checked_sub(dist[i][j])
-            .and_then(|x| x.checked_sub(1))
-            .unwrap_or(0);
-
-        if (mask & (1 << j)) == 0 || cur_minutes <= 0 {
-            continue;
-        }
+impl Network {
+    fn new(valves: &[SimpleValve], start: &str) -> Self {
+        let start = valves.iter().position(|x| x.name == start).unwrap();
+        let mut nodes = (0..valves.len())
+            .filter(|&i| valves[i].rate > 0)
+            .collect::<Vec<_>>();
+        let rates = nodes.iter().map(|&i| valves[i].rate).collect();
+        nodes.push(start);
 
-        let cur_mask = mask & !(1 << j);
-
-        let cur_flow = flow + (cur_minutes * valves[j].rate);
-
-        max_flow = max_flow.max(traveling_salesman(
-            valves,
-            memo,
-            non_zero_valves,
-            dist,
-            cur_mask,
-            cur_minutes,
-            cur_flow,
-            j,
-            depth + 1,
-        ));
+        let dist = nodes
+            .iter()
+            .map(|&from| {
+                let dist = bfs(valves, from);
+                nodes.iter().map(|&to| dist[to]).collect()
+            })
+            .collect();
+
+        Network { rates, dist }
     }
 
-    return max_flow;
-}
+    fn start(&self) -> usize {
+        self.rates.len()
+    }
+
+    fn best_per_set(&self, minutes: u32) -> Vec<u32> {
+        let mut best = vec![0; 1 << self.rates.len()];
+        self.explore(&mut best, 0, self.start(), minutes, 0);
+        best
+    }
 
-fn floyd_warshall(graph: Vec<Vec<u32>>) -> Vec<Vec<u32>> {
-    let l = graph.len();
-    let mut dist = graph.clone();
+    fn explore(&self, best: &mut [u32], open: usize, i: usize, minutes: u32, flow: u32) {
+        best[open] = best[open].max(flow);
 
-    for k in 0..l {
-        for i in 0..l {
-            for j in 0..l {
-                if dist[i][k] + dist[k][j] < dist[i][j] {
-                    dist[i][j] = dist[i][k] + dist[k][j];
-                }
+        for j in 0..self.rates.len() {
+            let cur_minutes = minutes.saturating_sub(self.dist[i][j].saturating_add(1));
+            if open & (1 << j) != 0 || cur_minutes == 0 {
+                continue;
             }
+
+            let cur_flow = flow + cur_minutes * self.rates[j];
+            self.explore(best, open | (1 << j), j, cur_minutes, cur_flow);
         }
     }
 
-    dist
-}
+    fn max_pressure(&self, minutes: u32, agents: usize) -> u32 {
+        if agents == 0 {
+            return 0;
+        }
+        if self.rates.len() > DENSE_VALVES {
+            return self.max_pressure_sparse(minutes, agents);
+        }
 
-fn init_graph<T, F>(list: &Vec<T>, get_links: F) -> Vec<Vec<u32>>
-where
-    F: Fn(&T) -> &Vec<usize>,
-{
-    let l = list.len();
-    let mut graph = vec![vec![u32::MAX / 4; l]; l];
+        let single = self.best_per_set(minutes);
+        let reachable = (0..single.len())
+            .filter(|&set| single[set] > 0)
+            .collect::<Vec<_>>();
 
-    list.iter().enumerate().for_each(|(i, x)| {
-        get_links(x).iter().for_each(|&j| graph[i][j as usize] = 1);
-    });
+        let mut combined = single.clone();
+        for bit in 0..self.rates.len() {
+            for mask in 0..combined.len() {
+                if mask & (1 << bit) != 0 {
+                    combined[mask] = combined[mask].max(combined[mask ^ (1 << bit)]);
+                }
+            }
+        }
 
-    graph
-}
+        // Sets that don't fit in the mask are skipped, which keeps `combined` a best-over-
+        // submasks table after every extra actor.
+        let full = single.len() - 1;
+        for _ in 2..agents {
+            combined = (0..combined.len())
+                .map(|mask| {
+                    reachable
+                        .iter()
+                        .filter(|&&set| set & !mask == 0)
+                        .map(|&set| single[set] + combined[mask ^ set])
+                        .fold(combined[mask], u32::max)
+                })
+                .collect();
+        }
+        if agents == 1 {
+            return combined[full];
+        }
 
-#[aoc(day16, part1)]
-pub fn part1(input: &str) -> u32 {
-    let valves = parse(input);
+        reachable
+            .iter()
+            .map(|&set| single[set] + combined[full ^ set])
+            .fold(combined[full], u32::max)
+    }
 
-    let graph = init_graph(&valves, |x| &x.links);
-    let dist = floyd_warshall(graph);
+    fn best_per_bitset(&self, minutes: u32) -> HashMap<BitSet, u32> {
+        let mut best = HashMap::new();
+        self.explore_bitset(&mut best, &mut BitSet::new(), self.start(), minutes, 0);
+        best
+    }
 
-    let start_idx = valves.iter().position(|x| x.name == "AA").unwrap();
-    let len = dist.len();
+    fn explore_bitset(
+        &self,
+        best: &mut HashMap<BitSet, u32>,
+        open: &mut BitSet,
+        i: usize,
+        minutes: u32,
+        flow: u32,
+    ) {
+        let entry = best.entry(open.clone()).or_insert(0);
+        *entry = (*entry).max(flow);
+
+        for j in 0..self.rates.len() {
+            let cur_minutes = minutes.saturating_sub(self.dist[i][j].saturating_add(1));
+            if open.contains(j) || cur_minutes == 0 {
+                continue;
+            }
 
-    // I spent 20 hours trying to find a bug that later appeared to be happening because of this line
-    // APPARENTLY THE STARTING VALVE IS ALWAYS "AA" AND NOT THE FIRST VALVE IN THE INPUT
-    let init_mask: u64 = (1 << len) - 1;
+            open.insert(j);
+            let cur_flow = flow + cur_minutes * self.rates[j];
+            self.explore_bitset(best, open, j, cur_minutes, cur_flow);
+            open.remove(j);
+        }
+    }
 
-    let (flow, _) = simulate(&valves, &dist, init_mask, start_idx, 30);
+    fn max_pressure_sparse(&self, minutes: u32, agents: usize) -> u32 {
+        let mut single = self
+            .best_per_bitset(minutes)
+            .into_iter()
+            .collect::<Vec<_>>();
+        single.sort_unstable_by_key(|&(_, flow)| Reverse(flow));
+
+        let mut combined = HashMap::from([(BitSet::new(), 0)]);
+        for _ in 1..agents {
+            let mut next = HashMap::new();
+            for (opened, flow) in &combined {
+                for (set, single_flow) in single.iter().filter(|(set, _)| set.is_disjoint(opened)) {
+                    let entry = next.entry(opened.union(set)).or_insert(0);
+                    *entry = (*entry).max(flow + single_flow);
+                }
+            }
+            combined = next;
+        }
 
-    flow
+        // `single` is sorted best first and holds the empty set, so the first disjoint set is
+        // always there and is the best one.
+        combined
+            .iter()
+            .map(|(opened, flow)| {
+                let (_, last) = single
+                    .iter()
+                    .find(|(set, _)| set.is_disjoint(opened))
+                    .unwrap();
+                flow + last
+            })
+            .max()
+            .unwrap()
+    }
 }
 
-#[aoc(day16, part2)]
-pub fn part2(input: &str) -> u32 {
-    let valves = parse(input);
+fn bfs(valves: &[SimpleValve], from: usize) -> Vec<u32> {
+    let mut dist = vec![u32::MAX; valves.len()];
+    let mut queue = VecDeque::from([from]);
+    dist[from] = 0;
 
-    let graph = init_graph(&valves, |x| &x.links);
-    let dist = floyd_warshall(graph);
+    while let Some(i) = queue.pop_front() {
+        for &j in &valves[i].links {
+            if dist[j] == u32::MAX {
+                dist[j] = dist[i] + 1;
+                queue.push_back(j);
+            }
+        }
+    }
 
-    let start_idx = valves.iter().position(|x| x.name == "AA").unwrap();
-    let init_mask: u64 = (1 << dist.len()) - 1;
+    dist
+}
 
-    let (_, elf_memo) = simulate(&valves, &dist, init_mask, start_idx, 26);
-    let (_, elephant_memo) = simulate(&valves, &dist, init_mask, start_idx, 26);
+pub fn max_pressure(input: &str, minutes: u32, agents: usize) -> u32 {
+    let valves = parse(input);
+    Network::new(&valves, "AA").max_pressure(minutes, agents)
+}
 
-    elf_memo.iter().fold(0, |max, (&elf_mask, &elf_flow)| {
-        elephant_memo
-            .iter()
-            .fold(max, |max, (&mask, &elephant_flow)| {
-                // Check that there's no overlap between the 2 paths
-                if (!mask) & (!elf_mask) & init_mask == 0 {
-                    return max.max(elephant_flow + elf_flow);
-                }
+#[aoc(day16, part1)]
+pub fn part1(input: &str) -> u32 {
+    max_pressure(input, 30, 1)
+}
 
-                max
-            })
-    })
+#[aoc(day16, part2)]
+pub fn part2(input: &str) -> u32 {
+    max_pressure(input, 26, 2)
 }
 
 #[cfg(test)]
@@ -323,4 +338,160 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 1707);
     }
+
+    // Minute-by-minute search where every actor either opens its valve or
+    // walks one tunnel.
+    fn brute_force(
+        valves: &[SimpleValve],
+        memo: &mut HashMap<(Vec<usize>, u32, u64), u32>,
+        at: Vec<usize>,
+        minutes: u32,
+        open: u64,
+    ) -> u32 {
+        if minutes == 0 {
+            return 0;
+        }
+        if let Some(&flow) = memo.get(&(at.clone(), minutes, open)) {
+            return flow;
+        }
+
+        let mut states = vec![(vec![], open, 0)];
+        for &i in &at {
+            let mut next = vec![];
+            for (moved, open, gain) in states {
+                if valves[i].rate > 0 && open & (1 << i) == 0 {
+                    let mut moved = moved.clone();
+                    moved.push(i);
+                    next.push((
+                        moved,
+                        open | (1 << i),
+                        gain + valves[i].rate * (minutes - 1),
+                    ));
+                }
+                for &j in &valves[i].links {
+                    let mut moved = moved.clone();
+                    moved.push(j);
+                    next.push((moved, open, gain));
+                }
+            }
+            states = next;
+        }
+
+        let best = states
+            .into_iter()
+            .map(|(mut moved, open, gain)| {
+                moved.sort();
+                gain + brute_force(valves, memo, moved, minutes - 1, open)
+            })
+            .max()
+            .unwrap();
+        memo.insert((at, minutes, open), best);
+        best
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = aoc_common::rng::Rng::new(16);
+        for size in [3, 5, 7] {
+            let input = crate::gen::day16(&mut rng, size);
+            let valves = parse(&input);
+            let start = valves.iter().position(|v| v.name == "AA").unwrap();
+            let mut memo = HashMap::new();
+            assert_eq!(
+                part1(&input),
+                brute_force(&valves, &mut memo, vec![start], 30, 0),
+                "{input}"
+            );
+            let mut memo = HashMap::new();
+            assert_eq!(
+                part2(&input),
+                brute_force(&valves, &mut memo, vec![start, start], 26, 0),
+                "{input}"
+            );
+        }
+        for size in [3, 4, 5] {
+            let input = crate::gen::day16(&mut rng, size);
+            let valves = parse(&input);
+            let start = valves.iter().position(|v| v.name == "AA").unwrap();
+            let mut memo = HashMap::new();
+            assert_eq!(
+                max_pressure(&input, 12, 3),
+                brute_force(&valves, &mut memo, vec![start; 3], 12, 0),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_many_valves() {
+        // Hang a chain of 80 broken valves off GG; the answers must not change.
+        let names = (0..80)
+            .map(|i| format!("{}{}", (b'K' + i / 26) as char, (b'A' + i % 26) as char))
+            .collect::<Vec<_>>();
+        let mut input = SAMPLE.replace("valves FF, HH", &format!("valves FF, HH, {}", names[0]));
+        for (i, name) in names.iter().enumerate() {
+            let prev = if i == 0 { "GG" } else { &names[i - 1] };
+            match names.get(i + 1) {
+                Some(next) => {
+                    input += &format!(
+                        "Valve {name} has flow rate=0; tunnels lead to valves {prev}, {next}\n"
+                    )
+                }
+                None => {
+                    input +=
+                        &format!("Valve {name} has flow rate=0; tunnel leads to valve {prev}\n")
+                }
+            }
+        }
+
+        assert_eq!(part1(&input), 1651);
+        assert_eq!(part2(&input), 1707);
+    }
+
+    #[test]
+    fn test_sparse_matches_dense() {
+        let mut rng = aoc_common::rng::Rng::new(61);
+        for size in [5, 10, 15] {
+            let input = crate::gen::day16(&mut rng, size);
+            let network = Network::new(&parse(&input), "AA");
+            for (minutes, agents) in [(30, 1), (26, 2), (16, 3)] {
+                assert_eq!(
+                    network.max_pressure_sparse(minutes, agents),
+                    network.max_pressure(minutes, agents),
+                    "{input}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_many_useful_valves() {
+        // `n` valves one tunnel away from AA and two from each other, with rates 1..=n. In 8
+        // minutes an actor opens its first valve with 6 minutes left and its second with 3.
+        let star = |n: u8| {
+            let names = (0..n)
+                .map(|i| format!("{}{}", (b'K' + i / 26) as char, (b'A' + i % 26) as char))
+                .collect::<Vec<_>>();
+            let mut input = format!(
+                "Valve AA has flow rate=0; tunnels lead to valves {}\n",
+                names.join(", ")
+            );
+            for (i, name) in names.iter().enumerate() {
+                input += &format!(
+                    "Valve {name} has flow rate={}; tunnel leads to valve AA\n",
+                    i + 1
+                );
+            }
+            input
+        };
+
+        let input = star(30);
+        assert_eq!(
+            max_pressure(&input, 8, 3),
+            6 * (30 + 29 + 28) + 3 * (27 + 26 + 25)
+        );
+        let input = star(70);
+        assert_eq!(max_pressure(&input, 8, 1), 6 * 70 + 3 * 69);
+        assert_eq!(max_pressure(&input, 8, 2), 6 * (70 + 69) + 3 * (68 + 67));
+    }
 }
@@ -1,237 +1,279 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
 
+use aoc_common::viz::{Frame, Recording, Visualize};
 use aoc_runner_derive::aoc;
 
+const ROCKS: &str = "
+####
+
+.#.
+###
+.#.
+
+..#
+..#
+###
+
+#
+#
+#
+#
+
+##
+##
+";
+
+// Rows of the reachable surface kept for cycle detection. The real inputs never leave a path
+// deeper than about 60 rows, but a column that never gets capped would otherwise make the key grow
+// with the tower and never repeat, so past this depth only the top rows are compared.
+const SURFACE_DEPTH: usize = 128;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rock {
+    // Bottom row first, each row stored as `words` words where bit x is column x counted from
+    // the left edge of the rock
+    rows: Vec<u64>,
+    width: usize,
+    words: usize,
+}
+
+impl Rock {
+    pub fn from(art: &str) -> Self {
+        let lines = art.trim().lines().map(str::trim).collect::<Vec<_>>();
+        let width = lines.iter().map(|line| line.len()).max().unwrap();
+        let words = width.div_ceil(64);
+        let mut rows = vec![0; lines.len() * words];
+        for (y, line) in lines.iter().rev().enumerate() {
+            for (x, b) in line.bytes().enumerate() {
+                match b {
+                    b'#' => rows[y * words + x / 64] |= 1 << (x % 64),
+                    b'.' => {}
+                    _ => panic!("Unknown rock cell: {}", b as char),
+                }
+            }
+        }
+        Rock { rows, width, words }
+    }
+
+    pub fn parse_all(art: &str) -> Vec<Rock> {
+        art.trim().split("\n\n").map(Rock::from).collect()
+    }
+}
+
+// The words of `row` shifted left by `x` bits, each paired with the index of the word it lands
+// in. Words that spill past the end are zero as long as the shifted row fits.
+fn shifted(row: &[u64], x: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+    let (offset, shift) = (x / 64, x % 64);
+    row.iter().enumerate().flat_map(move |(i, &word)| {
+        let carry = if shift == 0 { 0 } else { word >> (64 - shift) };
+        [(offset + i, word << shift), (offset + i + 1, carry)]
+    })
+}
+
 #[derive(Debug, Clone)]
-struct Game<'a> {
-    tower: Vec<u8>,
+pub struct Game<'a> {
+    width: usize,
+    words: usize,
+    tower: Vec<u64>,
 
     jets: &'a [u8],
     jet_index: usize,
 
-    rocks: &'a Vec<Vec<u8>>,
+    rocks: &'a [Rock],
     rock_index: usize,
-    rock_y: usize,
 }
 
 impl<'a> Game<'a> {
-    pub fn new(input: &'a str, rocks: &'a Vec<Vec<u8>>) -> Self {
+    pub fn new(input: &'a str, rocks: &'a [Rock], width: usize) -> Self {
+        assert!(
+            rocks.iter().all(|rock| rock.width + 2 <= width),
+            "Rock does not fit in the chamber"
+        );
         Game {
+            width,
+            words: width.div_ceil(64),
             tower: vec![],
-            jets: input.as_bytes(),
+            jets: input.trim().as_bytes(),
             jet_index: 0,
-            rocks: rocks,
+            rocks,
             rock_index: 0,
-            rock_y: 0,
         }
     }
 
-    pub fn next_rock(&mut self) -> Vec<u8> {
-        let rock = &self.rocks[self.rock_index];
-        self.rock_index += 1;
-        if self.rock_index >= self.rocks.len() {
-            self.rock_index = 0;
-        }
-
-        rock.clone()
+    pub fn height(&self) -> usize {
+        self.tower.len() / self.words
     }
 
-    pub fn make_space(&mut self, space: usize) {
-        self.rock_y = self.tower.len() + space;
+    fn row(&self, y: usize) -> Option<&[u64]> {
+        self.tower.get(y * self.words..(y + 1) * self.words)
     }
 
-    pub fn try_shift(&mut self, rock: &mut Vec<u8>) {
-        let jet = self.jets[self.jet_index];
-        self.jet_index += 1;
-        if self.jet_index >= self.jets.len() {
-            self.jet_index = 0;
-        }
+    pub fn drop_rock(&mut self) {
+        let rock = &self.rocks[self.rock_index];
+        self.rock_index = (self.rock_index + 1) % self.rocks.len();
 
-        match jet {
-            b'>' => {
-                if !self.hit_wall(rock, &0b00000001) {
-                    for row in rock.iter_mut() {
-                        *row >>= 1;
-                    }
-                    if self.hit_rock(rock, self.rock_y) {
-                        for row in rock.iter_mut() {
-                            *row <<= 1;
-                        }
-                    }
-                }
-            }
-            b'<' => {
-                if !self.hit_wall(rock, &0b01000000) {
-                    for row in rock.iter_mut() {
-                        *row <<= 1;
-                    }
-                    if self.hit_rock(rock, self.rock_y) {
-                        for row in rock.iter_mut() {
-                            *row >>= 1;
-                        }
-                    }
-                }
+        let (mut x, mut y) = (2usize, self.height() + 3);
+        loop {
+            let jet = self.jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.jets.len();
+
+            let shifted = match jet {
+                b'>' => Some(x + 1),
+                b'<' => x.checked_sub(1),
+                _ => panic!("Unknown jet direction: {}", jet as char),
+            };
+            if let Some(shifted) = shifted.filter(|&x| self.fits(rock, x, y)) {
+                x = shifted;
             }
-            _ => {
-                panic!("Unknown jet direction: {}", jet as char);
+
+            if y > 0 && self.fits(rock, x, y - 1) {
+                y -= 1;
+            } else {
+                break;
             }
         }
-    }
 
-    pub fn can_fall(&self, rock: &Vec<u8>) -> bool {
-        self.rock_y > 0 && !self.hit_rock(rock, self.rock_y - 1)
+        for (i, row) in rock.rows.chunks(rock.words).enumerate() {
+            if y + i == self.height() {
+                self.tower.resize(self.tower.len() + self.words, 0);
+            }
+            let tower_row = &mut self.tower[(y + i) * self.words..(y + i + 1) * self.words];
+            for (word, bits) in shifted(row, x).filter(|&(_, bits)| bits != 0) {
+                tower_row[word] |= bits;
+            }
+        }
     }
 
-    pub fn fall(&mut self) {
-        self.rock_y -= 1;
+    fn fits(&self, rock: &Rock, x: usize, y: usize) -> bool {
+        x + rock.width <= self.width
+            && rock.rows.chunks(rock.words).enumerate().all(|(i, row)| {
+                self.row(y + i).is_none_or(|tower_row| {
+                    shifted(row, x)
+                        .all(|(word, bits)| bits & tower_row.get(word).copied().unwrap_or(0) == 0)
+                })
+            })
     }
 
-    pub fn stack(&mut self, rock: &Vec<u8>) {
-        for (i, row) in rock.iter().enumerate() {
-            if self.rock_y + i < self.tower.len() {
-                self.tower[self.rock_y + i] |= row;
-            } else {
-                self.tower.push(*row);
-            }
+    // Every empty cell a falling rock could still reach, row by row from the top of the tower
+    // down to the deepest reachable row or `SURFACE_DEPTH`. Rocks only move left, right or down,
+    // so this decides everything that happens next.
+    fn surface(&self) -> Vec<u64> {
+        let mut full = vec![u64::MAX; self.words];
+        if self.width % 64 != 0 {
+            full[self.words - 1] = u64::MAX >> (64 - self.width % 64);
         }
-    }
-
-    fn hit_wall(&self, rock: &Vec<u8>, wall: &u8) -> bool {
-        for row in rock {
-            if row & wall != 0 {
-                return true;
+        let mut surface = vec![];
+        let mut reachable = full.clone();
+        let mut free = full.clone();
+        for row in self.tower.chunks(self.words).rev().take(SURFACE_DEPTH) {
+            for i in 0..self.words {
+                free[i] = !row[i] & full[i];
+                reachable[i] &= free[i];
             }
+            // Spreading in place only ever adds reachable cells, so repeat until nothing changes
+            let mut changed = true;
+            while changed {
+                changed = false;
+                for i in 0..self.words {
+                    let below = if i > 0 { reachable[i - 1] >> 63 } else { 0 };
+                    let above = reachable.get(i + 1).map_or(0, |word| word << 63);
+                    let word = reachable[i];
+                    let spread = (word | word << 1 | below | word >> 1 | above) & free[i];
+                    if spread != word {
+                        reachable[i] = spread;
+                        changed = true;
+                    }
+                }
+            }
+            if reachable.iter().all(|&word| word == 0) {
+                break;
+            }
+            surface.extend_from_slice(&reachable);
         }
-        false
+        surface
     }
+}
 
-    fn hit_rock(&self, rock: &Vec<u8>, rock_y: usize) -> bool {
-        for (i, row) in rock.iter().enumerate() {
-            if rock_y + i < self.tower.len() && (self.tower[rock_y + i] & row) != 0 {
-                return true;
+impl<'a> Visualize for Game<'a> {
+    fn frame(&self) -> Frame {
+        let mut rows = vec![];
+        for row in self.tower.chunks(self.words).rev() {
+            let mut line = String::from("|");
+            for x in 0..self.width {
+                line.push(if row[x / 64] & (1 << (x % 64)) != 0 {
+                    '#'
+                } else {
+                    '.'
+                });
             }
+            line.push('|');
+            rows.push(line);
         }
-        false
+        rows.push(format!("+{}+", "-".repeat(self.width)));
+        Frame::from_rows(rows.iter().map(|row| row.chars()))
     }
 }
 
 impl<'a> Display for Game<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for i in (0..self.tower.len()).rev() {
-            write!(f, "{:08b}\n", self.tower[i])?;
-        }
-        Ok(())
+        write!(f, "{}", self.frame())
     }
 }
 
-fn get_rocks() -> Vec<Vec<u8>> {
-    let mut rocks = vec![];
-
-    /*
-    ####
-    */
-    rocks.push(vec![0b00011110]);
-
-    /*
-    .#.
-    ###
-    .#.
-    */
-    rocks.push(vec![0b00001000, 0b00011100, 0b00001000]);
-
-    /*
-    ..#
-    ..#
-    ###
-    */
-    rocks.push(vec![0b00011100, 0b00000100, 0b00000100]);
-
-    /*
-    #
-    #
-    #
-    #
-    */
-    rocks.push(vec![0b00010000, 0b00010000, 0b00010000, 0b00010000]);
-
-    /*
-    ##
-    ##
-    */
-    rocks.push(vec![0b00011000, 0b00011000]);
-
-    rocks
-}
-
-#[aoc(day17, part1)]
-pub fn part1(input: &str) -> usize {
-    let rocks = get_rocks();
-    let mut game = Game::new(input, &rocks);
-
-    for _ in 0..2022 {
-        let mut rock = game.next_rock();
-        game.make_space(3);
+pub fn visualize(input: &str, rocks: usize) -> Vec<Frame> {
+    let shapes = Rock::parse_all(ROCKS);
+    let mut game = Game::new(input, &shapes, 7);
+    let mut recording = Recording::new();
 
-        loop {
-            game.try_shift(&mut rock);
-
-            if game.can_fall(&rock) {
-                game.fall();
-            } else {
-                game.stack(&rock);
-                break;
-            }
-        }
+    for _ in 0..rocks {
+        game.drop_rock();
+        recording.capture(&game);
     }
 
-    game.tower.len()
+    recording.into_frames()
 }
 
-#[aoc(day17, part2)]
-pub fn part2(input: &str) -> usize {
-    let rocks = get_rocks();
-    let mut game = Game::new(input, &rocks);
+pub fn tower_height(input: &str, rocks: &[Rock], width: usize, total_rocks: usize) -> usize {
+    let mut game = Game::new(input, rocks, width);
 
+    let mut pairs = HashSet::new();
     let mut seen = HashMap::new();
+    let mut skipped = false;
     let mut added_height = 0;
     let mut i = 0;
-    let total_rocks = 1_000_000_000_000usize;
 
     while i < total_rocks {
-        let mut rock = game.next_rock();
-        game.make_space(3);
+        game.drop_rock();
+        i += 1;
 
-        loop {
-            game.try_shift(&mut rock);
+        // Only a rock and jet pair seen before can close a cycle, so the surface waits until then
+        if !skipped && !pairs.insert((game.rock_index, game.jet_index)) {
+            let state = (game.rock_index, game.jet_index, game.surface());
+            if let Some((old_i, old_height)) = seen.insert(state, (i, game.height())) {
+                let cycle_len = i - old_i;
+                let cycles = (total_rocks - i) / cycle_len;
 
-            if game.can_fall(&rock) {
-                game.fall();
-            } else {
-                game.stack(&rock);
-                break;
+                added_height = cycles * (game.height() - old_height);
+                i += cycles * cycle_len;
+                skipped = true;
             }
         }
+    }
 
-        // Only keep the top N rows for state (e.g., 20)
-        let n = 20;
-        let top = game.tower.iter().rev().take(n).cloned().collect::<Vec<_>>();
-        let state = (game.rock_index, game.jet_index, top);
-
-        if let Some(&(old_i, old_height)) = seen.get(&state) {
-            let cycle_len = i - old_i;
-            let cycle_height = game.tower.len() - old_height;
-            let cycles = (total_rocks - i) / cycle_len;
-
-            added_height += cycles * cycle_height;
-            i += cycles * cycle_len;
-        } else {
-            seen.insert(state, (i, game.tower.len()));
-        }
+    game.height() + added_height
+}
 
-        i += 1;
-    }
+#[aoc(day17, part1)]
+pub fn part1(input: &str) -> usize {
+    tower_height(input, &Rock::parse_all(ROCKS), 7, 2022)
+}
 
-    game.tower.len() + added_height
+#[aoc(day17, part2)]
+pub fn part2(input: &str) -> usize {
+    tower_height(input, &Rock::parse_all(ROCKS), 7, 1_000_000_000_000)
 }
 
 #[cfg(test)]
@@ -250,4 +292,101 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 1514285714288);
     }
+
+    #[test]
+    fn test_rock_from_art() {
+        let rock = Rock::from("..#\n..#\n###");
+        assert_eq!(rock.rows, [0b111, 0b100, 0b100]);
+        assert_eq!(rock.width, 3);
+        assert_eq!(Rock::parse_all(ROCKS).len(), 5);
+    }
+
+    #[test]
+    fn test_variant_matches_plain_simulation() {
+        let rocks = Rock::parse_all(indoc! {"
+            #####
+
+            #.#
+            ###
+
+            #
+            #
+        "});
+        for total in [1, 100, 3000] {
+            let mut game = Game::new(SAMPLE, &rocks, 11);
+            (0..total).for_each(|_| game.drop_rock());
+            assert_eq!(tower_height(SAMPLE, &rocks, 11, total), game.height());
+        }
+    }
+
+    #[test]
+    fn test_generated_jets() {
+        let rocks = Rock::parse_all(ROCKS);
+        let mut rng = aoc_common::rng::Rng::new(17);
+        for size in [5, 20, 50] {
+            let input = crate::gen::day17(&mut rng, size);
+            let mut game = Game::new(&input, &rocks, 7);
+            for total in 1..=3000 {
+                game.drop_rock();
+                if total % 500 == 0 {
+                    assert_eq!(
+                        tower_height(&input, &rocks, 7, total),
+                        game.height(),
+                        "{input}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_chamber() {
+        let wide = Rock::from(&"#".repeat(70));
+        let mut game = Game::new(">", std::slice::from_ref(&wide), 72);
+        game.drop_rock();
+        game.drop_rock();
+        let row = format!("|..{}|", "#".repeat(70));
+        assert_eq!(
+            game.to_string(),
+            format!("{row}\n{row}\n+{}+\n", "-".repeat(72))
+        );
+
+        let mut rocks = Rock::parse_all(ROCKS);
+        rocks.push(wide);
+        for total in [1, 100, 1000] {
+            let mut game = Game::new(SAMPLE, &rocks, 100);
+            (0..total).for_each(|_| game.drop_rock());
+            assert_eq!(tower_height(SAMPLE, &rocks, 100, total), game.height());
+        }
+    }
+
+    #[test]
+    fn test_open_chimney() {
+        // Every rock is blown against the left wall, leaving the right columns open to the floor
+        let rocks = [Rock::from("####")];
+        for total in [10, 1000] {
+            let mut game = Game::new("<", &rocks, 7);
+            (0..total).for_each(|_| game.drop_rock());
+            assert_eq!(tower_height("<", &rocks, 7, total), game.height());
+        }
+        assert_eq!(
+            tower_height("<", &rocks, 7, 1_000_000_000_000),
+            1_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn test_visualize() {
+        let frames = visualize(SAMPLE, 2);
+        assert_eq!(
+            frames[1].to_string(),
+            indoc! {"
+                |...#...|
+                |..###..|
+                |...#...|
+                |..####.|
+                +-------+
+            "}
+        );
+    }
 }
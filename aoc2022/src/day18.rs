@@ -1,14 +1,24 @@
 use aoc_runner_derive::aoc;
-use std::collections::{HashSet, VecDeque};
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Write},
+};
 
 type Pos = (i32, i32, i32);
 
-fn parse(input: &str) -> HashSet<Pos> {
+fn parse(input: &str) -> Vec<Pos> {
     input
         .lines()
-        .filter_map(|line| {
-            let mut nums = line.split(',').filter_map(|n| n.parse().ok());
-            Some((nums.next()?, nums.next()?, nums.next()?))
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let nums = line
+                .split(',')
+                .map(|n| n.trim().parse().ok())
+                .collect::<Option<Vec<_>>>();
+            match nums.as_deref() {
+                Some(&[x, y, z]) => (x, y, z),
+                _ => panic!("Invalid cube: {line}"),
+            }
         })
         .collect()
 }
@@ -22,70 +32,227 @@ const DIRS: [(i32, i32, i32); 6] = [
     (0, 0, -1),
 ];
 
-#[aoc(day18, part1)]
-pub fn part1(input: &str) -> usize {
-    let cubes = parse(input);
-    cubes
-        .iter()
-        .map(|&(x, y, z)| {
-            DIRS.iter()
-                .filter(|&&(dx, dy, dz)| !cubes.contains(&(x + dx, y + dy, z + dz)))
-                .count()
-        })
-        .sum()
+// Air cells are labelled by the component they belong to; the padded border guarantees the
+// first cell is outside the droplet, so label 0 is the exterior.
+const EXTERIOR: u32 = 0;
+const LAVA: u32 = u32::MAX;
+const UNSEEN: u32 = u32::MAX - 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pocket {
+    pub cells: Vec<Pos>,
 }
 
-#[aoc(day18, part2)]
-pub fn part2(input: &str) -> usize {
-    let cubes = parse(input);
-
-    // Find bounding box
-    let min_x = cubes.iter().map(|&(x, _, _)| x).min().unwrap();
-    let max_x = cubes.iter().map(|&(x, _, _)| x).max().unwrap();
-    let min_y = cubes.iter().map(|&(_, y, _)| y).min().unwrap();
-    let max_y = cubes.iter().map(|&(_, y, _)| y).max().unwrap();
-    let min_z = cubes.iter().map(|&(_, _, z)| z).min().unwrap();
-    let max_z = cubes.iter().map(|&(_, _, z)| z).max().unwrap();
-
-    // Flood fill from outside the bounding box
-    let mut visited = HashSet::new();
-    let mut queue = VecDeque::new();
-    queue.push_back((min_x - 1, min_y - 1, min_z - 1));
-    visited.insert((min_x - 1, min_y - 1, min_z - 1));
-
-    while let Some((x, y, z)) = queue.pop_front() {
-        for (dx, dy, dz) in DIRS {
-            let nx = x + dx;
-            let ny = y + dy;
-            let nz = z + dz;
-            // Stay within expanded bounding box
-            if nx < min_x - 1
-                || nx > max_x + 1
-                || ny < min_y - 1
-                || ny > max_y + 1
-                || nz < min_z - 1
-                || nz > max_z + 1
-            {
+impl Pocket {
+    pub fn volume(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Voxels {
+    min: Pos,
+    size: (usize, usize, usize),
+    labels: Vec<u32>,
+    pockets: usize,
+}
+
+impl Voxels {
+    pub fn from(input: &str) -> Self {
+        let cubes = parse(input);
+        if cubes.is_empty() {
+            return Voxels {
+                min: (0, 0, 0),
+                size: (0, 0, 0),
+                labels: vec![],
+                pockets: 0,
+            };
+        }
+        let min = cubes.iter().fold((i32::MAX, i32::MAX, i32::MAX), |m, c| {
+            (m.0.min(c.0 - 1), m.1.min(c.1 - 1), m.2.min(c.2 - 1))
+        });
+        let max = cubes.iter().fold((i32::MIN, i32::MIN, i32::MIN), |m, c| {
+            (m.0.max(c.0 + 1), m.1.max(c.1 + 1), m.2.max(c.2 + 1))
+        });
+        let size = (
+            (max.0 - min.0 + 1) as usize,
+            (max.1 - min.1 + 1) as usize,
+            (max.2 - min.2 + 1) as usize,
+        );
+
+        let mut voxels = Voxels {
+            min,
+            size,
+            labels: vec![UNSEEN; size.0 * size.1 * size.2],
+            pockets: 0,
+        };
+        for &cube in &cubes {
+            let i = voxels.index(cube).unwrap();
+            voxels.labels[i] = LAVA;
+        }
+        voxels.label_air();
+        voxels
+    }
+
+    fn index(&self, (x, y, z): Pos) -> Option<usize> {
+        let (x, y, z) = (x - self.min.0, y - self.min.1, z - self.min.2);
+        if x < 0 || y < 0 || z < 0 {
+            return None;
+        }
+        let (x, y, z) = (x as usize, y as usize, z as usize);
+        if x >= self.size.0 || y >= self.size.1 || z >= self.size.2 {
+            return None;
+        }
+        Some((z * self.size.1 + y) * self.size.0 + x)
+    }
+
+    fn pos(&self, i: usize) -> Pos {
+        let x = i % self.size.0;
+        let y = i / self.size.0 % self.size.1;
+        let z = i / self.size.0 / self.size.1;
+        (
+            x as i32 + self.min.0,
+            y as i32 + self.min.1,
+            z as i32 + self.min.2,
+        )
+    }
+
+    fn label(&self, pos: Pos) -> u32 {
+        self.index(pos).map_or(EXTERIOR, |i| self.labels[i])
+    }
+
+    fn label_air(&mut self) {
+        let mut next = EXTERIOR;
+        for start in 0..self.labels.len() {
+            if self.labels[start] != UNSEEN {
                 continue;
             }
-            let np = (nx, ny, nz);
-            if cubes.contains(&np) || visited.contains(&np) {
-                continue;
+            self.labels[start] = next;
+            let mut queue = VecDeque::from([start]);
+            while let Some(i) = queue.pop_front() {
+                let (x, y, z) = self.pos(i);
+                for (dx, dy, dz) in DIRS {
+                    if let Some(j) = self.index((x + dx, y + dy, z + dz)) {
+                        if self.labels[j] == UNSEEN {
+                            self.labels[j] = next;
+                            queue.push_back(j);
+                        }
+                    }
+                }
             }
-            visited.insert(np);
-            queue.push_back(np);
+            next += 1;
         }
+        self.pockets = next as usize - 1;
     }
 
-    // Count faces adjacent to outside air
-    cubes
-        .iter()
-        .map(|&(x, y, z)| {
-            DIRS.iter()
-                .filter(|&&(dx, dy, dz)| visited.contains(&(x + dx, y + dy, z + dz)))
-                .count()
-        })
-        .sum()
+    pub fn contains(&self, pos: Pos) -> bool {
+        self.label(pos) == LAVA
+    }
+
+    // Every lava face, with the label of the air cell it touches
+    fn faces(&self) -> impl Iterator<Item = (Pos, (i32, i32, i32), u32)> + '_ {
+        (0..self.labels.len())
+            .filter(|&i| self.labels[i] == LAVA)
+            .flat_map(move |i| {
+                let (x, y, z) = self.pos(i);
+                DIRS.iter().filter_map(move |&(dx, dy, dz)| {
+                    let label = self.label((x + dx, y + dy, z + dz));
+                    (label != LAVA).then_some(((x, y, z), (dx, dy, dz), label))
+                })
+            })
+    }
+
+    pub fn total_surface(&self) -> usize {
+        self.faces().count()
+    }
+
+    pub fn exterior_surface(&self) -> usize {
+        self.faces()
+            .filter(|&(_, _, label)| label == EXTERIOR)
+            .count()
+    }
+
+    pub fn pockets(&self) -> Vec<Pocket> {
+        let mut pockets = vec![Pocket { cells: vec![] }; self.pockets];
+        for (i, &label) in self.labels.iter().enumerate() {
+            if label != LAVA && label != EXTERIOR {
+                pockets[label as usize - 1].cells.push(self.pos(i));
+            }
+        }
+        pockets
+    }
+
+    // Quads of the requested surface, wound counter-clockwise when seen from outside the lava
+    fn quads(&self, exterior_only: bool) -> Vec<([i32; 3], [[i32; 3]; 4])> {
+        self.faces()
+            .filter(|&(_, _, label)| !exterior_only || label == EXTERIOR)
+            .map(|((x, y, z), (dx, dy, dz), _)| {
+                let normal = [dx, dy, dz];
+                let a = normal.iter().position(|&n| n != 0).unwrap();
+                let (b, c) = ((a + 1) % 3, (a + 2) % 3);
+                let mut corners = [(0, 0), (1, 0), (1, 1), (0, 1)];
+                if normal[a] < 0 {
+                    corners.reverse();
+                }
+                let quad = corners.map(|(db, dc)| {
+                    let mut corner = [x, y, z];
+                    corner[a] += i32::from(normal[a] > 0);
+                    corner[b] += db;
+                    corner[c] += dc;
+                    corner
+                });
+                (normal, quad)
+            })
+            .collect()
+    }
+
+    pub fn write_obj(&self, out: &mut impl Write, exterior_only: bool) -> io::Result<()> {
+        let mut vertices = HashMap::new();
+        let mut faces = vec![];
+        for (_, quad) in self.quads(exterior_only) {
+            let face = quad.map(|corner| {
+                let next = vertices.len() + 1;
+                *vertices.entry(corner).or_insert(next)
+            });
+            faces.push(face);
+        }
+
+        let mut ordered = vertices.into_iter().collect::<Vec<_>>();
+        ordered.sort_unstable_by_key(|&(_, i)| i);
+        for ([x, y, z], _) in ordered {
+            writeln!(out, "v {x} {y} {z}")?;
+        }
+        for [a, b, c, d] in faces {
+            writeln!(out, "f {a} {b} {c} {d}")?;
+        }
+        Ok(())
+    }
+
+    pub fn write_stl(&self, out: &mut impl Write, exterior_only: bool) -> io::Result<()> {
+        writeln!(out, "solid droplet")?;
+        for ([nx, ny, nz], quad) in self.quads(exterior_only) {
+            for triangle in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                writeln!(out, "  facet normal {nx} {ny} {nz}")?;
+                writeln!(out, "    outer loop")?;
+                for [x, y, z] in triangle {
+                    writeln!(out, "      vertex {x} {y} {z}")?;
+                }
+                writeln!(out, "    endloop")?;
+                writeln!(out, "  endfacet")?;
+            }
+        }
+        writeln!(out, "endsolid droplet")
+    }
+}
+
+#[aoc(day18, part1)]
+pub fn part1(input: &str) -> usize {
+    Voxels::from(input).total_surface()
+}
+
+#[aoc(day18, part2)]
+pub fn part2(input: &str) -> usize {
+    Voxels::from(input).exterior_surface()
 }
 
 #[cfg(test)]
@@ -118,4 +285,75 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 58);
     }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(part1(""), 0);
+        assert_eq!(part2("\n"), 0);
+        assert!(Voxels::from("").pockets().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid cube: 1,x,3")]
+    fn test_invalid_cube() {
+        part1("1,2,3\n1,x,3");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid cube: 1,2")]
+    fn test_missing_coordinate() {
+        part1("1,2,3\n1,2");
+    }
+
+    #[test]
+    fn test_pockets() {
+        let voxels = Voxels::from(SAMPLE);
+        assert!(voxels.contains((2, 2, 6)) && !voxels.contains((2, 2, 5)));
+        let pockets = voxels.pockets();
+        assert_eq!(
+            pockets,
+            [Pocket {
+                cells: vec![(2, 2, 5)]
+            }]
+        );
+        assert_eq!(pockets[0].volume(), 1);
+
+        let hollow = (0..27)
+            .map(|i| (i % 3, i / 3 % 3, i / 9))
+            .filter(|&p| p != (1, 1, 1))
+            .map(|(x, y, z)| format!("{x},{y},{z}\n"))
+            .collect::<String>();
+        let voxels = Voxels::from(&hollow);
+        assert_eq!(voxels.pockets()[0].volume(), 1);
+        assert_eq!(voxels.total_surface(), 54 + 6);
+        assert_eq!(voxels.exterior_surface(), 54);
+    }
+
+    #[test]
+    fn test_mesh() {
+        let cube = Voxels::from("1,1,1");
+        let mut obj = vec![];
+        cube.write_obj(&mut obj, true).unwrap();
+        let obj = String::from_utf8(obj).unwrap();
+        assert_eq!(obj.lines().filter(|l| l.starts_with("v ")).count(), 8);
+        assert_eq!(obj.lines().filter(|l| l.starts_with("f ")).count(), 6);
+
+        let mut stl = vec![];
+        cube.write_stl(&mut stl, true).unwrap();
+        let stl = String::from_utf8(stl).unwrap();
+        assert_eq!(stl.matches("facet normal").count(), 12);
+
+        // Outward winding makes the signed volume of the closed surface equal the lava volume
+        let voxels = Voxels::from(SAMPLE);
+        let signed_volume = voxels
+            .quads(false)
+            .iter()
+            .flat_map(|(_, q)| [[q[0], q[1], q[2]], [q[0], q[2], q[3]]])
+            .map(|[a, b, c]| {
+                a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0])
+            })
+            .sum::<i32>();
+        assert_eq!(signed_volume, 13 * 6);
+    }
 }
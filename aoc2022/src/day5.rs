@@ -1,68 +1,167 @@
 use aoc_runner_derive::aoc;
+use std::fmt::Display;
 
-#[aoc(day5, part1)]
-pub fn part1(input: &str) -> String {
-    let (drawing, moves) = input.split_once("\n\n").unwrap();
-    let mut lines = drawing.lines().rev();
-    let stack_count = lines.next().unwrap().split_whitespace().count();
-    let mut stacks: Vec<Vec<char>> = vec![Vec::new(); stack_count];
-
-    for line in drawing.lines().rev().skip(1) {
-        for (i, stack) in stacks.iter_mut().enumerate() {
-            let idx = 1 + i * 4;
-            if let Some(c) = line.chars().nth(idx) {
-                if c.is_ascii_alphabetic() {
-                    stack.push(c);
-                }
-            }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stacks {
+    stacks: Vec<Vec<String>>,
+    width: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub count: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+pub trait Crane {
+    fn apply(&self, stacks: &mut Stacks, mv: &Move);
+}
+
+pub struct CrateMover9000;
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9000 {
+    fn apply(&self, stacks: &mut Stacks, mv: &Move) {
+        for _ in 0..mv.count {
+            let label = stacks.take(mv.from, 1);
+            stacks.put(mv.to, label);
         }
     }
+}
 
-    for line in moves.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        let count: usize = parts[1].parse().unwrap();
-        let from: usize = parts[3].parse::<usize>().unwrap() - 1;
-        let to: usize = parts[5].parse::<usize>().unwrap() - 1;
-        for _ in 0..count {
-            if let Some(c) = stacks[from].pop() {
-                stacks[to].push(c);
+impl Crane for CrateMover9001 {
+    fn apply(&self, stacks: &mut Stacks, mv: &Move) {
+        let labels = stacks.take(mv.from, mv.count);
+        stacks.put(mv.to, labels);
+    }
+}
+
+impl Stacks {
+    // Every cell is `width` characters wide (`[label]` or blanks) and cells are
+    // separated by one space, so the stack numbers below may have any number of
+    // digits.
+    pub fn from(drawing: &str) -> Self {
+        let mut lines = drawing.lines().rev();
+        let count = lines.next().unwrap().split_whitespace().count();
+        let width = drawing
+            .find('[')
+            .map(|start| drawing[start..].find(']').unwrap() + 1)
+            .unwrap_or(3);
+
+        let mut stacks = vec![Vec::new(); count];
+        for line in lines {
+            for (i, stack) in stacks.iter_mut().enumerate() {
+                let start = i * (width + 1);
+                let Some(cell) = line.get(start..line.len().min(start + width)) else {
+                    continue;
+                };
+                if let Some(label) = cell.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+                    stack.push(label.to_string());
+                } else {
+                    assert!(cell.trim().is_empty(), "bad crate `{cell}` in `{line}`");
+                }
             }
         }
+        Stacks { stacks, width }
+    }
+
+    pub fn stacks(&self) -> &[Vec<String>] {
+        &self.stacks
+    }
+
+    pub fn top(&self) -> String {
+        self.stacks
+            .iter()
+            .filter_map(|s| s.last())
+            .cloned()
+            .collect()
+    }
+
+    fn take(&mut self, stack: usize, count: usize) -> Vec<String> {
+        let stack = &mut self.stacks[stack];
+        assert!(count <= stack.len(), "not enough crates to move");
+        stack.split_off(stack.len() - count)
     }
 
-    stacks.iter_mut().map(|s| s.pop().unwrap()).collect()
+    fn put(&mut self, stack: usize, labels: Vec<String>) {
+        for label in &labels {
+            self.width = self.width.max(label.len() + 2);
+        }
+        self.stacks[stack].extend(labels);
+    }
 }
 
-#[aoc(day5, part2)]
-pub fn part2(input: &str) -> String {
-    let (drawing, moves) = input.split_once("\n\n").unwrap();
-    let mut lines = drawing.lines().rev();
-    let stack_count = lines.next().unwrap().split_whitespace().count();
-    let mut stacks: Vec<Vec<char>> = vec![Vec::new(); stack_count];
-
-    for line in drawing.lines().rev().skip(1) {
-        for (i, stack) in stacks.iter_mut().enumerate() {
-            let idx = 1 + i * 4;
-            if let Some(c) = line.chars().nth(idx) {
-                if c.is_ascii_alphabetic() {
-                    stack.push(c);
-                }
-            }
+impl Display for Stacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let width = self.width;
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let cells = self
+                .stacks
+                .iter()
+                .map(|stack| match stack.get(row) {
+                    Some(label) => format!("{:^width$}", format!("[{label}]")),
+                    None => " ".repeat(width),
+                })
+                .collect::<Vec<_>>();
+            writeln!(f, "{}", cells.join(" "))?;
         }
+        let numbers = (1..=self.stacks.len())
+            .map(|n| format!("{n:^width$}"))
+            .collect::<Vec<_>>();
+        write!(f, "{}", numbers.join(" "))
     }
+}
 
-    for line in moves.lines() {
+impl Move {
+    pub fn from(line: &str) -> Self {
         let parts: Vec<&str> = line.split_whitespace().collect();
-        let count: usize = parts[1].parse().unwrap();
-        let from: usize = parts[3].parse::<usize>().unwrap() - 1;
-        let to: usize = parts[5].parse::<usize>().unwrap() - 1;
+        assert!(
+            parts.len() == 6 && parts[0] == "move" && parts[2] == "from" && parts[4] == "to",
+            "bad move `{line}`"
+        );
+        Move {
+            count: parts[1].parse().unwrap(),
+            from: parts[3].parse::<usize>().unwrap() - 1,
+            to: parts[5].parse::<usize>().unwrap() - 1,
+        }
+    }
+}
 
-        let split_point = stacks[from].len() - count;
-        let mut crates: Vec<char> = stacks[from].drain(split_point..).collect();
-        stacks[to].append(&mut crates);
+pub fn parse(input: &str) -> (Stacks, Vec<Move>) {
+    let (drawing, moves) = input.split_once("\n\n").unwrap();
+    (
+        Stacks::from(drawing),
+        moves.lines().map(Move::from).collect(),
+    )
+}
+
+// Yields the starting stacks followed by the stacks after every move.
+pub fn replay(input: &str, crane: impl Crane) -> impl Iterator<Item = Stacks> {
+    let (stacks, moves) = parse(input);
+    std::iter::once(stacks.clone()).chain(moves.into_iter().scan(stacks, move |stacks, mv| {
+        crane.apply(stacks, &mv);
+        Some(stacks.clone())
+    }))
+}
+
+pub fn rearrange(input: &str, crane: impl Crane) -> String {
+    let (mut stacks, moves) = parse(input);
+    for mv in &moves {
+        crane.apply(&mut stacks, mv);
     }
+    stacks.top()
+}
 
-    stacks.iter_mut().map(|s| s.pop().unwrap()).collect()
+#[aoc(day5, part1)]
+pub fn part1(input: &str) -> String {
+    rearrange(input, CrateMover9000)
+}
+
+#[aoc(day5, part2)]
+pub fn part2(input: &str) -> String {
+    rearrange(input, CrateMover9001)
 }
 
 #[cfg(test)]
@@ -92,4 +191,45 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), "MCD");
     }
+
+    #[test]
+    fn test_round_trip() {
+        // The sample's number line loses its leading space to indoc
+        let drawing = ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "].join("\n");
+        let (stacks, _) = parse(SAMPLE);
+        assert_eq!(stacks.to_string(), drawing);
+        assert_eq!(Stacks::from(&drawing), stacks);
+    }
+
+    #[test]
+    fn test_many_stacks() {
+        let drawing = [
+            "                                            [L]",
+            "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [K] [M]",
+            " 1   2   3   4   5   6   7   8   9  10  11  12 ",
+        ]
+        .join("\n");
+        let stacks = Stacks::from(&drawing);
+        assert_eq!(stacks.to_string(), drawing);
+        assert_eq!(stacks.stacks()[11], ["M", "L"]);
+
+        let input = format!("{drawing}\n\nmove 2 from 12 to 10\nmove 1 from 10 to 1");
+        assert_eq!(part1(&input), "MBCDEFGHILK");
+        assert_eq!(part2(&input), "LBCDEFGHIMK");
+    }
+
+    #[test]
+    fn test_replay() {
+        let states = replay(SAMPLE, CrateMover9000).collect::<Vec<_>>();
+        let tops = states.iter().map(Stacks::top).collect::<Vec<_>>();
+        assert_eq!(tops, ["NDP", "DCP", "CZ", "MZ", "CMZ"]);
+        let expected = [
+            "        [Z]",
+            "        [N]",
+            "    [C] [D]",
+            "    [M] [P]",
+            " 1   2   3 ",
+        ];
+        assert_eq!(states[2].to_string(), expected.join("\n"));
+    }
 }
@@ -1,118 +1,329 @@
-use std::collections::HashMap;
+use std::fmt::Display;
 
 use aoc_runner_derive::aoc;
 
+const ROOT: usize = 0;
+
 #[derive(Debug)]
-enum Node {
+enum Kind {
     File(usize),
-    Dir(HashMap<String, Node>),
+    Dir { children: Vec<usize>, listed: bool },
 }
 
-impl Node {
-    fn get_dir_mut<'a>(&'a mut self, path: &[String]) -> Option<&'a mut Node> {
-        let mut node = self;
-        for p in path {
-            match node {
-                Node::Dir(map) => node = map.get_mut(p)?,
-                Node::File(_) => return None,
-            }
-        }
-        Some(node)
-    }
+#[derive(Debug)]
+struct Node {
+    name: String,
+    parent: usize,
+    kind: Kind,
+}
 
-    fn collect_dir_sizes(&self, sizes: &mut Vec<usize>) -> usize {
-        match self {
-            Node::File(size) => *size,
-            Node::Dir(map) => {
-                let sum = map.values().map(|n| n.collect_dir_sizes(sizes)).sum();
-                sizes.push(sum);
-                sum
-            }
-        }
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
     }
 }
 
-#[aoc(day7, part1)]
-pub fn part1(input: &str) -> usize {
-    let mut root = Node::Dir(HashMap::new());
-    let mut cwd: Vec<String> = Vec::new();
-
-    for line in input.lines() {
-        if line.starts_with("$ cd ") {
-            let arg = &line[5..];
-            match arg {
-                "/" => cwd.clear(),
-                ".." => {
-                    cwd.pop();
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry<'a> {
+    pub path: String,
+    pub name: &'a str,
+    pub size: usize,
+    pub is_dir: bool,
+}
+
+#[derive(Debug)]
+pub struct FileTree {
+    nodes: Vec<Node>,
+    sizes: Vec<usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+struct Listing {
+    dir: usize,
+    relisted: bool,
+    seen: Vec<usize>,
+}
+
+impl FileTree {
+    pub fn from(transcript: &str) -> Self {
+        let mut tree = FileTree {
+            nodes: vec![Node {
+                name: String::from("/"),
+                parent: ROOT,
+                kind: Kind::Dir {
+                    children: vec![],
+                    listed: false,
+                },
+            }],
+            sizes: vec![],
+            diagnostics: vec![],
+        };
+
+        let mut cwd = ROOT;
+        let mut listing: Option<Listing> = None;
+        for (i, line) in transcript.lines().enumerate() {
+            let line_no = i + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            if let Some(command) = line.strip_prefix("$ ") {
+                if let Some(listing) = listing.take() {
+                    tree.finish_listing(listing, line_no);
                 }
-                dir => cwd.push(dir.to_string()),
+                match command.split_once(' ') {
+                    Some(("cd", "/")) => cwd = ROOT,
+                    Some(("cd", "..")) => {
+                        if cwd == ROOT {
+                            tree.report(line_no, "cd .. from the root directory");
+                        }
+                        cwd = tree.nodes[cwd].parent;
+                    }
+                    Some(("cd", name)) => cwd = tree.enter(cwd, name, line_no),
+                    None if command == "ls" => {
+                        let relisted = match &mut tree.nodes[cwd].kind {
+                            Kind::Dir { listed, .. } => std::mem::replace(listed, true),
+                            Kind::File(_) => unreachable!(),
+                        };
+                        listing = Some(Listing {
+                            dir: cwd,
+                            relisted,
+                            seen: vec![],
+                        });
+                    }
+                    _ => tree.report(line_no, &format!("unknown command `{command}`")),
+                }
+                continue;
+            }
+
+            let Some(listing) = listing.as_mut() else {
+                tree.report(line_no, &format!("output `{line}` outside of an ls"));
+                continue;
+            };
+            let Some((info, name)) = line.split_once(' ') else {
+                tree.report(line_no, &format!("malformed entry `{line}`"));
+                continue;
+            };
+            let kind = match info {
+                "dir" => Kind::Dir {
+                    children: vec![],
+                    listed: false,
+                },
+                size => match size.parse() {
+                    Ok(size) => Kind::File(size),
+                    Err(_) => {
+                        tree.report(line_no, &format!("bad file size `{size}`"));
+                        continue;
+                    }
+                },
+            };
+            if let Some(child) = tree.add_child(listing.dir, name, kind, line_no) {
+                listing.seen.push(child);
             }
-        } else if line.starts_with("$ ls") {
-            // ignore
-        } else if line.starts_with("dir ") {
-            let dir_name = &line[4..];
-            let dir = root.get_dir_mut(&cwd).unwrap();
-            if let Node::Dir(map) = dir {
-                map.entry(dir_name.to_string())
-                    .or_insert(Node::Dir(HashMap::new()));
+        }
+        if let Some(listing) = listing.take() {
+            tree.finish_listing(listing, transcript.lines().count() + 1);
+        }
+
+        tree.sizes = vec![0; tree.nodes.len()];
+        // Children are always created after their parent, so a reverse sweep sums bottom-up
+        for i in (0..tree.nodes.len()).rev() {
+            if let Kind::File(size) = tree.nodes[i].kind {
+                tree.sizes[i] = size;
             }
-        } else if !line.is_empty() {
-            let (size, name) = line.split_once(' ').unwrap();
-            let size: usize = size.parse().unwrap();
-            let dir = root.get_dir_mut(&cwd).unwrap();
-            if let Node::Dir(map) = dir {
-                map.entry(name.to_string()).or_insert(Node::File(size));
+            if i != ROOT {
+                tree.sizes[tree.nodes[i].parent] += tree.sizes[i];
             }
         }
+
+        tree
     }
 
-    let mut sizes = Vec::new();
-    root.collect_dir_sizes(&mut sizes);
-    sizes.into_iter().filter(|&s| s <= 100_000).sum()
-}
+    fn report(&mut self, line: usize, message: &str) {
+        self.diagnostics.push(Diagnostic {
+            line,
+            message: message.to_string(),
+        });
+    }
 
-#[aoc(day7, part2)]
-pub fn part2(input: &str) -> usize {
-    let mut root = Node::Dir(HashMap::new());
-    let mut cwd: Vec<String> = Vec::new();
-
-    for line in input.lines() {
-        if line.starts_with("$ cd ") {
-            let arg = &line[5..];
-            match arg {
-                "/" => cwd.clear(),
-                ".." => {
-                    cwd.pop();
-                }
-                dir => cwd.push(dir.to_string()),
+    fn children(&self, dir: usize) -> &[usize] {
+        match &self.nodes[dir].kind {
+            Kind::Dir { children, .. } => children,
+            Kind::File(_) => &[],
+        }
+    }
+
+    fn child(&self, dir: usize, name: &str) -> Option<usize> {
+        self.children(dir)
+            .iter()
+            .copied()
+            .find(|&c| self.nodes[c].name == name)
+    }
+
+    fn enter(&mut self, cwd: usize, name: &str, line: usize) -> usize {
+        match self.child(cwd, name) {
+            Some(child) if matches!(self.nodes[child].kind, Kind::File(_)) => {
+                let path = self.path(child);
+                self.report(line, &format!("cd into file {path}"));
+                cwd
+            }
+            Some(child) => child,
+            None => {
+                let path = self.path(cwd);
+                self.report(
+                    line,
+                    &format!("cd into `{name}`, which was never listed in {path}"),
+                );
+                let kind = Kind::Dir {
+                    children: vec![],
+                    listed: false,
+                };
+                self.add_child(cwd, name, kind, line).unwrap_or(cwd)
             }
-        } else if line.starts_with("$ ls") {
-            // ignore
-        } else if line.starts_with("dir ") {
-            let dir_name = &line[4..];
-            let dir = root.get_dir_mut(&cwd).unwrap();
-            if let Node::Dir(map) = dir {
-                map.entry(dir_name.to_string())
-                    .or_insert(Node::Dir(HashMap::new()));
+        }
+    }
+
+    fn add_child(&mut self, dir: usize, name: &str, kind: Kind, line: usize) -> Option<usize> {
+        if let Some(existing) = self.child(dir, name) {
+            match (&self.nodes[existing].kind, &kind) {
+                (Kind::Dir { .. }, Kind::Dir { .. }) => {}
+                (Kind::File(old), Kind::File(new)) if old == new => {}
+                (Kind::File(old), Kind::File(new)) => {
+                    let path = self.path(existing);
+                    self.report(line, &format!("{path} listed as {new} bytes, was {old}"));
+                }
+                _ => {
+                    let path = self.path(existing);
+                    self.report(line, &format!("{path} listed as both a file and a dir"));
+                }
             }
-        } else if !line.is_empty() {
-            let (size, name) = line.split_once(' ').unwrap();
-            let size: usize = size.parse().unwrap();
-            let dir = root.get_dir_mut(&cwd).unwrap();
-            if let Node::Dir(map) = dir {
-                map.entry(name.to_string()).or_insert(Node::File(size));
+            return Some(existing);
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: dir,
+            kind,
+        });
+        match &mut self.nodes[dir].kind {
+            Kind::Dir { children, .. } => children.push(id),
+            Kind::File(_) => unreachable!(),
+        }
+        Some(id)
+    }
+
+    fn finish_listing(&mut self, listing: Listing, line: usize) {
+        if !listing.relisted {
+            return;
+        }
+        let missing = self
+            .children(listing.dir)
+            .iter()
+            .copied()
+            .filter(|c| !listing.seen.contains(c))
+            .collect::<Vec<_>>();
+        for child in missing {
+            let path = self.path(child);
+            self.report(line, &format!("{path} is missing from a repeated listing"));
+        }
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    fn path(&self, mut id: usize) -> String {
+        if id == ROOT {
+            return String::from("/");
+        }
+        let mut names = vec![];
+        while id != ROOT {
+            names.push(self.nodes[id].name.as_str());
+            id = self.nodes[id].parent;
+        }
+        names
+            .iter()
+            .rev()
+            .fold(String::new(), |path, name| path + "/" + name)
+    }
+
+    fn lookup(&self, path: &str) -> Option<usize> {
+        path.split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(ROOT, |dir, name| self.child(dir, name))
+    }
+
+    pub fn du(&self, path: &str) -> Option<usize> {
+        self.lookup(path).map(|id| self.sizes[id])
+    }
+
+    pub fn find(&self, predicate: impl Fn(&Entry) -> bool) -> Vec<Entry<'_>> {
+        (0..self.nodes.len())
+            .map(|id| Entry {
+                path: self.path(id),
+                name: &self.nodes[id].name,
+                size: self.sizes[id],
+                is_dir: matches!(self.nodes[id].kind, Kind::Dir { .. }),
+            })
+            .filter(|entry| predicate(entry))
+            .collect()
+    }
+
+    fn print(&self, f: &mut std::fmt::Formatter<'_>, id: usize, depth: usize) -> std::fmt::Result {
+        let node = &self.nodes[id];
+        match node.kind {
+            Kind::File(size) => writeln!(
+                f,
+                "{}- {} (file, size={size})",
+                "  ".repeat(depth),
+                node.name
+            ),
+            Kind::Dir { .. } => {
+                writeln!(f, "{}- {} (dir)", "  ".repeat(depth), node.name)?;
+                self.children(id)
+                    .iter()
+                    .try_for_each(|&child| self.print(f, child, depth + 1))
             }
         }
     }
+}
+
+impl Display for FileTree {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.print(f, ROOT, 0)
+    }
+}
 
-    let mut sizes = Vec::new();
-    let used = root.collect_dir_sizes(&mut sizes);
+#[aoc(day7, part1)]
+pub fn part1(input: &str) -> usize {
+    let tree = FileTree::from(input);
+    tree.find(|entry| entry.is_dir && entry.size <= 100_000)
+        .iter()
+        .map(|entry| entry.size)
+        .sum()
+}
+
+#[aoc(day7, part2)]
+pub fn part2(input: &str) -> usize {
+    let tree = FileTree::from(input);
+    let used = tree.du("/").unwrap();
     let total = 70_000_000;
     let needed = 30_000_000;
     let unused = total - used;
     let must_free = needed - unused;
 
-    sizes.into_iter().filter(|&s| s >= must_free).min().unwrap()
+    tree.find(|entry| entry.is_dir && entry.size >= must_free)
+        .iter()
+        .map(|entry| entry.size)
+        .min()
+        .unwrap()
 }
 
 #[cfg(test)]
@@ -156,4 +367,79 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 24933642);
     }
+
+    #[test]
+    fn test_tree() {
+        let tree = FileTree::from(SAMPLE);
+        assert!(tree.diagnostics().is_empty());
+        assert_eq!(
+            tree.to_string(),
+            indoc! {"
+                - / (dir)
+                  - a (dir)
+                    - e (dir)
+                      - i (file, size=584)
+                    - f (file, size=29116)
+                    - g (file, size=2557)
+                    - h.lst (file, size=62596)
+                  - b.txt (file, size=14848514)
+                  - c.dat (file, size=8504156)
+                  - d (dir)
+                    - j (file, size=4060174)
+                    - d.log (file, size=8033020)
+                    - d.ext (file, size=5626152)
+                    - k (file, size=7214296)
+            "}
+        );
+        assert_eq!(tree.du("/a"), Some(94853));
+        assert_eq!(tree.du("/a/e/"), Some(584));
+        assert_eq!(tree.du("/x"), None);
+
+        let logs = tree.find(|entry| entry.name.starts_with("d."));
+        let paths = logs.iter().map(|e| e.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["/d/d.log", "/d/d.ext"]);
+    }
+
+    #[test]
+    fn test_repeated_listing() {
+        let input = format!("{SAMPLE}$ cd /\n$ ls\ndir a\n14848514 b.txt\n8504156 c.dat\ndir d\n");
+        let tree = FileTree::from(&input);
+        assert!(tree.diagnostics().is_empty());
+        assert_eq!(part1(&input), 95437);
+        assert_eq!(part2(&input), 24933642);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let tree = FileTree::from(indoc! {"
+            $ cd /
+            $ ls
+            dir a
+            10 b
+            $ cd c
+            $ cd ..
+            $ cd b
+            $ ls
+            20 b
+            dir c
+            $ cd ..
+            $ pwd
+        "});
+        let messages = tree
+            .diagnostics()
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            [
+                "line 5: cd into `c`, which was never listed in /",
+                "line 7: cd into file /b",
+                "line 9: /b listed as 20 bytes, was 10",
+                "line 11: /a is missing from a repeated listing",
+                "line 11: cd .. from the root directory",
+                "line 12: unknown command `pwd`",
+            ]
+        );
+    }
 }
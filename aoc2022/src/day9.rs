@@ -1,77 +1,126 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::RangeInclusive};
 
+use aoc_common::viz::Frame;
 use aoc_runner_derive::aoc;
 
-#[aoc(day9, part1)]
-pub fn part1(input: &str) -> usize {
-    let mut head = (0i32, 0i32);
-    let mut tail = (0i32, 0i32);
-    let mut visited = HashSet::new();
-    visited.insert(tail);
+const START: (i32, i32) = (0, 0);
 
-    for line in input.lines() {
-        let (dir, n) = line.split_once(' ').unwrap();
-        let n: i32 = n.parse().unwrap();
-        let (dx, dy) = match dir {
-            "R" => (1, 0),
-            "L" => (-1, 0),
-            "U" => (0, 1),
-            "D" => (0, -1),
-            _ => unreachable!(),
-        };
-        for _ in 0..n {
-            head.0 += dx;
-            head.1 += dy;
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rope {
+    knots: Vec<(i32, i32)>,
+}
+
+impl Rope {
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "A rope needs at least one knot");
+        Rope {
+            knots: vec![START; len],
+        }
+    }
 
-            let (hx, hy) = head;
-            let (tx, ty) = tail;
+    pub fn knots(&self) -> &[(i32, i32)] {
+        &self.knots
+    }
+
+    pub fn step(&mut self, (dx, dy): (i32, i32)) {
+        self.knots[0].0 += dx;
+        self.knots[0].1 += dy;
+        for i in 1..self.knots.len() {
+            let (hx, hy) = self.knots[i - 1];
+            let (tx, ty) = self.knots[i];
             let dx = hx - tx;
             let dy = hy - ty;
             if dx.abs() > 1 || dy.abs() > 1 {
-                tail.0 += dx.signum();
-                tail.1 += dy.signum();
+                self.knots[i].0 += dx.signum();
+                self.knots[i].1 += dy.signum();
             }
-            visited.insert(tail);
         }
     }
-    visited.len()
+
+    fn label(&self, i: usize) -> char {
+        match i {
+            0 => 'H',
+            1 if self.knots.len() == 2 => 'T',
+            i => char::from_digit(i as u32, 36).unwrap_or('*'),
+        }
+    }
+
+    // Earlier knots are drawn over later ones, and `s` marks the start if nothing covers it.
+    pub fn render(&self, xs: RangeInclusive<i32>, ys: RangeInclusive<i32>) -> Frame {
+        let mut cells = vec![(START, 's')];
+        cells.extend(
+            self.knots
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(i, &knot)| (knot, self.label(i))),
+        );
+        draw(cells, xs, ys)
+    }
 }
 
-#[aoc(day9, part2)]
-pub fn part2(input: &str) -> usize {
-    use std::collections::HashSet;
+fn draw(
+    cells: impl IntoIterator<Item = ((i32, i32), char)>,
+    xs: RangeInclusive<i32>,
+    ys: RangeInclusive<i32>,
+) -> Frame {
+    let width = (xs.end() - xs.start() + 1).max(0) as usize;
+    let height = (ys.end() - ys.start() + 1).max(0) as usize;
+    let mut frame = Frame::new(width, height);
+    for ((x, y), c) in cells {
+        if xs.contains(&x) && ys.contains(&y) {
+            frame.set((x - xs.start()) as usize, (ys.end() - y) as usize, c);
+        }
+    }
+    frame
+}
 
-    let mut knots = [(0i32, 0i32); 10];
-    let mut visited = HashSet::new();
-    visited.insert(knots[9]);
+pub fn render_visited(
+    visited: &HashSet<(i32, i32)>,
+    xs: RangeInclusive<i32>,
+    ys: RangeInclusive<i32>,
+) -> Frame {
+    let cells = visited.iter().map(|&cell| (cell, '#'));
+    draw(cells.chain([(START, 's')]), xs, ys)
+}
 
-    for line in input.lines() {
+fn motions(input: &str) -> impl Iterator<Item = (i32, i32)> + '_ {
+    input.lines().flat_map(|line| {
         let (dir, n) = line.split_once(' ').unwrap();
-        let n: i32 = n.parse().unwrap();
-        let (dx, dy) = match dir {
+        let n: usize = n.parse().unwrap();
+        let step = match dir {
             "R" => (1, 0),
             "L" => (-1, 0),
             "U" => (0, 1),
             "D" => (0, -1),
             _ => unreachable!(),
         };
-        for _ in 0..n {
-            knots[0].0 += dx;
-            knots[0].1 += dy;
-            for i in 1..10 {
-                let (hx, hy) = knots[i - 1];
-                let (tx, ty) = knots[i];
-                let dx = hx - tx;
-                let dy = hy - ty;
-                if dx.abs() > 1 || dy.abs() > 1 {
-                    knots[i].0 += dx.signum();
-                    knots[i].1 += dy.signum();
-                }
-            }
-            visited.insert(knots[9]);
-        }
-    }
-    visited.len()
+        std::iter::repeat_n(step, n)
+    })
+}
+
+pub fn simulate(input: &str, len: usize) -> impl Iterator<Item = Rope> + '_ {
+    motions(input).scan(Rope::new(len), |rope, step| {
+        rope.step(step);
+        Some(rope.clone())
+    })
+}
+
+pub fn visited(input: &str, len: usize, knot: usize) -> HashSet<(i32, i32)> {
+    assert!(knot < len, "Knot {knot} is not on a rope of {len}");
+    let mut visited = HashSet::from([START]);
+    visited.extend(simulate(input, len).map(|rope| rope.knots[knot]));
+    visited
+}
+
+#[aoc(day9, part1)]
+pub fn part1(input: &str) -> usize {
+    visited(input, 2, 1).len()
+}
+
+#[aoc(day9, part2)]
+pub fn part2(input: &str) -> usize {
+    visited(input, 10, 9).len()
 }
 
 #[cfg(test)]
@@ -102,6 +151,11 @@ mod tests {
         U 20
     "};
 
+    #[test]
+    fn test_part1() {
+        assert_eq!(part1(SAMPLE1), 13);
+    }
+
     #[test]
     fn test_part2_small() {
         assert_eq!(part2(SAMPLE1), 1);
@@ -111,4 +165,50 @@ mod tests {
     fn test_part2_large() {
         assert_eq!(part2(SAMPLE2), 36);
     }
+
+    #[test]
+    fn test_render() {
+        let rope = simulate(SAMPLE1, 2).nth(3).unwrap();
+        assert_eq!(
+            rope.render(0..=5, 0..=4).to_string(),
+            indoc! {"
+            ......
+            ......
+            ......
+            ......
+            s..TH.
+        "}
+        );
+
+        let rope = simulate(SAMPLE1, 10).nth(7).unwrap();
+        assert_eq!(
+            rope.render(0..=5, 0..=4).to_string(),
+            indoc! {"
+            ....H.
+            ....1.
+            ..432.
+            .5....
+            6.....
+        "}
+        );
+
+        let visited = visited(SAMPLE1, 2, 1);
+        assert_eq!(
+            render_visited(&visited, 0..=5, 0..=4).to_string(),
+            indoc! {"
+            ..##..
+            ...##.
+            .####.
+            ....#.
+            s###..
+        "}
+        );
+    }
+
+    #[test]
+    fn test_visited_by_knot() {
+        assert_eq!(visited(SAMPLE2, 10, 0).len(), visited(SAMPLE2, 1, 0).len());
+        assert_eq!(visited(SAMPLE2, 10, 1).len(), visited(SAMPLE2, 2, 1).len());
+        assert_eq!(visited(SAMPLE2, 10, 9).len(), 36);
+    }
 }
@@ -0,0 +1,426 @@
+use aoc_common::rng::Rng;
+
+// Every day has a generator except day 10, whose part 2 has to draw letters
+// the OCR knows, and day 15, whose part 2 needs exactly one uncovered spot in
+// a 4000000-wide square.
+
+fn grid(rng: &mut Rng, height: usize, width: usize, cell: impl Fn(&mut Rng) -> char) -> String {
+    (0..height)
+        .map(|_| (0..width).map(|_| cell(rng)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(3))
+        .map(|_| join_lines((0..1 + rng.below(5)).map(|_| rng.range(1000..=60000).to_string())))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        format!(
+            "{} {}",
+            *rng.choose(&['A', 'B', 'C']),
+            *rng.choose(&['X', 'Y', 'Z'])
+        )
+    }))
+}
+
+// Both compartments of a rucksack share at least one item, and every group of
+// three shares a badge.
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let items = (b'a'..=b'z')
+        .chain(b'A'..=b'Z')
+        .map(char::from)
+        .collect::<Vec<_>>();
+    let mut lines = vec![];
+    for _ in 0..size.max(1) {
+        let badge = *rng.choose(&items);
+        for _ in 0..3 {
+            let half = 2 + rng.below(10);
+            let mut first = (0..half).map(|_| *rng.choose(&items)).collect::<Vec<_>>();
+            let mut second = (0..half).map(|_| *rng.choose(&items)).collect::<Vec<_>>();
+            let shared = *rng.choose(&items);
+            first[0] = shared;
+            second[0] = shared;
+            first[1] = badge;
+            rng.shuffle(&mut first);
+            rng.shuffle(&mut second);
+            lines.push(first.into_iter().chain(second).collect());
+        }
+    }
+    join_lines(lines)
+}
+
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        let (a, b) = (rng.range(1..=99), rng.range(1..=99));
+        let (c, d) = (rng.range(1..=99), rng.range(1..=99));
+        format!("{}-{},{}-{}", a.min(b), a.max(b), c.min(d), c.max(d))
+    }))
+}
+
+// Moves are generated against the current stack heights, so no move takes more
+// crates than its stack holds.
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let count = 3 + rng.below(7);
+    let mut stacks = (0..count)
+        .map(|_| {
+            (0..1 + rng.below(6))
+                .map(|_| char::from(b'A' + rng.below(26) as u8))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let height = stacks.iter().map(Vec::len).max().unwrap();
+    let mut drawing = (0..height)
+        .rev()
+        .map(|row| {
+            stacks
+                .iter()
+                .map(|stack| {
+                    stack
+                        .get(row)
+                        .map_or(String::from("   "), |c| format!("[{c}]"))
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+    drawing.push(
+        (1..=count)
+            .map(|n| format!(" {n} "))
+            .collect::<Vec<_>>()
+            .join(" "),
+    );
+
+    let mut moves = vec![];
+    for _ in 0..size {
+        let from = rng.below(count);
+        if stacks[from].is_empty() {
+            continue;
+        }
+        let to = (from + 1 + rng.below(count - 1)) % count;
+        let moved = 1 + rng.below(stacks[from].len());
+        let at = stacks[from].len() - moved;
+        let crates = stacks[from].split_off(at);
+        stacks[to].extend(crates);
+        moves.push(format!("move {moved} from {} to {}", from + 1, to + 1));
+    }
+
+    format!("{}\n\n{}", drawing.join("\n"), join_lines(moves))
+}
+
+// The solvers assume a marker exists, so the stream always ends with fourteen
+// distinct characters.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    let mut letters = (b'a'..=b'z').map(char::from).collect::<Vec<_>>();
+    let mut stream = (0..size)
+        .map(|_| *rng.choose(&letters[..6]))
+        .collect::<String>();
+    rng.shuffle(&mut letters);
+    stream.extend(&letters[..14]);
+    stream
+}
+
+struct Dir {
+    files: Vec<(usize, String)>,
+    dirs: Vec<(String, Dir)>,
+}
+
+// `names` counts every entry so far, which keeps names unique.
+fn dir(rng: &mut Rng, depth: usize, budget: &mut usize, names: &mut usize, max_file: i64) -> Dir {
+    let mut files = vec![];
+    let mut dirs = vec![];
+    for _ in 0..1 + rng.below(4) {
+        *names += 1;
+        let name = format!("n{names}");
+        if depth < 5 && *budget > 0 && rng.chance(0.4) {
+            *budget -= 1;
+            dirs.push((name, dir(rng, depth + 1, budget, names, max_file)));
+        } else {
+            files.push((rng.range(1000..=max_file) as usize, format!("{name}.txt")));
+        }
+    }
+    Dir { files, dirs }
+}
+
+fn du(dir: &Dir) -> usize {
+    let files = dir.files.iter().map(|(size, _)| size).sum::<usize>();
+    files + dir.dirs.iter().map(|(_, dir)| du(dir)).sum::<usize>()
+}
+
+fn transcript(dir: &Dir, lines: &mut Vec<String>) {
+    lines.push(String::from("$ ls"));
+    lines.extend(dir.dirs.iter().map(|(name, _)| format!("dir {name}")));
+    lines.extend(
+        dir.files
+            .iter()
+            .map(|(size, name)| format!("{size} {name}")),
+    );
+    for (name, dir) in &dir.dirs {
+        lines.push(format!("$ cd {name}"));
+        transcript(dir, lines);
+        lines.push(String::from("$ cd .."));
+    }
+}
+
+// Part 2 needs between 40000000 and 70000000 used, so the random files add up
+// to at most 20000000 and a large file in the root tops them up.
+pub fn day7(rng: &mut Rng, size: usize) -> String {
+    let mut budget = size.max(1);
+    let max_file = (20_000_000 / (4 * (budget + 1))).max(1000) as i64;
+    let mut root = dir(rng, 0, &mut budget, &mut 0, max_file);
+    root.files
+        .push((45_000_000 - du(&root), String::from("big.dat")));
+
+    let mut lines = vec![String::from("$ cd /")];
+    transcript(&root, &mut lines);
+    join_lines(lines)
+}
+
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| {
+        char::from(b'0' + rng.below(10) as u8)
+    })
+}
+
+pub fn day9(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        format!(
+            "{} {}",
+            *rng.choose(&['U', 'D', 'L', 'R']),
+            rng.range(1..=20)
+        )
+    }))
+}
+
+// Only one monkey multiplies, so the plain worry levels of part 1 stay within a
+// `u64` for its 20 rounds.
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+    rng.shuffle(&mut primes);
+    let count = size.clamp(2, primes.len());
+    (0..count)
+        .map(|i| {
+            let items = (0..1 + rng.below(4))
+                .map(|_| rng.range(50..=99).to_string())
+                .collect::<Vec<_>>();
+            let op = if i == 0 {
+                format!("old * {}", rng.range(2..=19))
+            } else {
+                format!("old + {}", rng.range(1..=8))
+            };
+            let if_true = (i + 1 + rng.below(count - 1)) % count;
+            let if_false = (i + 1 + rng.below(count - 1)) % count;
+            format!(
+                "Monkey {i}:\n  Starting items: {}\n  Operation: new = {op}\n  Test: divisible by {}\n    If true: throw to monkey {if_true}\n    If false: throw to monkey {if_false}",
+                items.join(", "),
+                primes[i]
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// Every other row is a climbing trail from `S` to `E` that rises at most one
+// level per step, so both parts always find a path. The rows in between are
+// random.
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    let (height, width) = (size.max(8) | 1, size.max(8));
+    let mut trail = vec![];
+    for row in (0..height).step_by(2) {
+        let cols = (0..width).collect::<Vec<_>>();
+        if row % 4 == 0 {
+            trail.extend(cols.iter().map(|&col| (row, col)));
+        } else {
+            trail.extend(cols.iter().rev().map(|&col| (row, col)));
+        }
+        if row + 1 < height {
+            trail.push((row + 1, trail.last().unwrap().1));
+        }
+    }
+
+    let mut grid = (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| b'a' + rng.below(26) as u8)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for (k, &(row, col)) in trail.iter().enumerate() {
+        grid[row][col] = b'a' + (k * 25 / (trail.len() - 1)) as u8;
+    }
+    let (start, end) = (trail[0], trail[trail.len() - 1]);
+    grid[start.0][start.1] = b'S';
+    grid[end.0][end.1] = b'E';
+    join_lines(grid.into_iter().map(|row| String::from_utf8(row).unwrap()))
+}
+
+fn packet(rng: &mut Rng, depth: usize) -> String {
+    let items = (0..rng.below(5))
+        .map(|_| {
+            if depth < 3 && rng.chance(0.3) {
+                packet(rng, depth + 1)
+            } else {
+                rng.range(0..=10).to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", items.join(","))
+}
+
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| format!("{}\n{}", packet(rng, 0), packet(rng, 0)))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size.max(1)).map(|_| {
+        let (mut x, mut y) = (
+            rng.range(490..=510) as usize,
+            rng.range(2..=(2 + size as i64)) as usize,
+        );
+        let mut points = vec![format!("{x},{y}")];
+        for i in 0..1 + rng.below(3) {
+            let step = rng.range(1..=6) as usize;
+            if i % 2 == 0 {
+                x = if rng.chance(0.5) { x + step } else { x - step };
+            } else {
+                y += step;
+            }
+            points.push(format!("{x},{y}"));
+        }
+        points.join(" -> ")
+    }))
+}
+
+// The tunnels form a random spanning tree over `size` valves plus a few extra
+// links, so every valve is reachable from `AA`. Links only join valves close
+// together in the order they were made, so like the real inputs the cave is a
+// long winding corridor and only a handful of valves are within reach in 30
+// minutes, however large it gets. About a quarter of the valves have a non-zero
+// flow rate, also as in the real inputs, so past `size` 256 there are usually
+// more useful valves than fit in a 64-bit mask.
+pub fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut names = (0..26 * 26)
+        .map(|i| String::from_utf8(vec![b'A' + (i / 26) as u8, b'A' + (i % 26) as u8]).unwrap())
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut names[1..]);
+
+    let size = size.clamp(2, names.len());
+    let mut links = vec![vec![]; size];
+    let mut connect = |a: usize, b: usize| {
+        if a != b && !links[a].contains(&b) {
+            links[a].push(b);
+            links[b].push(a);
+        }
+    };
+    for i in 1..size {
+        connect(i, i - 1 - rng.below(i.min(3)));
+    }
+    for _ in 0..size / 3 {
+        let a = rng.below(size);
+        connect(a, (a + 2 + rng.below(3)).min(size - 1));
+    }
+
+    let mut lines = (0..size)
+        .map(|i| {
+            let rate = if i == 0 || !rng.chance(0.25) {
+                0
+            } else {
+                rng.range(1..=25)
+            };
+            let to = links[i]
+                .iter()
+                .map(|&j| names[j].as_str())
+                .collect::<Vec<_>>();
+            let tunnels = if to.len() == 1 {
+                "tunnel leads to valve"
+            } else {
+                "tunnels lead to valves"
+            };
+            format!(
+                "Valve {} has flow rate={rate}; {tunnels} {}",
+                names[i],
+                to.join(", ")
+            )
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut lines);
+    join_lines(lines)
+}
+
+// Jets push left and right equally often. A stream that mostly pushes one way
+// leaves an open chimney along the far wall, and the tower never repeats.
+pub fn day17(rng: &mut Rng, size: usize) -> String {
+    let half = size.max(20);
+    let mut jets = [vec!['<'; half], vec!['>'; half]].concat();
+    rng.shuffle(&mut jets);
+    jets.into_iter().collect()
+}
+
+pub fn day18(rng: &mut Rng, size: usize) -> String {
+    let mut cubes = (0..size * size)
+        .map(|_| (rng.range(1..=8), rng.range(1..=8), rng.range(1..=8)))
+        .collect::<Vec<_>>();
+    cubes.sort();
+    cubes.dedup();
+    rng.shuffle(&mut cubes);
+    join_lines(cubes.iter().map(|(x, y, z)| format!("{x},{y},{z}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    // Only checks that every solver accepts what the generators make. The
+    // answers are compared against reference solutions in the days' own tests.
+    #[test]
+    fn test_generated_inputs_smoke() {
+        let mut rng = Rng::new(2022);
+        for size in [5, 20] {
+            let input = day1(&mut rng, size);
+            let _ = (day1::part1(&input), day1::part2(&input));
+            let input = day2(&mut rng, size);
+            let _ = (day2::part1(&input), day2::part2(&input));
+            let input = day3(&mut rng, size);
+            let _ = (day3::part1(&input), day3::part2(&input));
+            let input = day4(&mut rng, size);
+            let _ = (day4::part1(&input), day4::part2(&input));
+            let input = day5(&mut rng, size);
+            let _ = (day5::part1(&input), day5::part2(&input));
+            let input = day6(&mut rng, size);
+            let _ = (day6::part1(&input), day6::part2(&input));
+            let input = day7(&mut rng, size);
+            let _ = (day7::part1(&input), day7::part2(&input));
+            let input = day8(&mut rng, size);
+            let _ = (day8::part1(&input), day8::part2(&input));
+            let input = day9(&mut rng, size);
+            let _ = (day9::part1(&input), day9::part2(&input));
+            let input = day11(&mut rng, size);
+            let _ = (day11::part1(&input), day11::part2(&input));
+            let input = day12(&mut rng, size);
+            let _ = (day12::part1(&input), day12::part2(&input));
+            let input = day13(&mut rng, size);
+            let _ = (day13::part1(&input), day13::part2(&input));
+            let input = day14(&mut rng, size);
+            let _ = (day14::part1(&input), day14::part2(&input));
+            let input = day16(&mut rng, size);
+            let _ = (day16::part1(&input), day16::part2(&input));
+            let input = day17(&mut rng, size);
+            let _ = (day17::part1(&input), day17::part2(&input));
+            let input = day18(&mut rng, size);
+            let _ = (day18::part1(&input), day18::part2(&input));
+        }
+    }
+}
@@ -1,153 +1,184 @@
-use std::{
-    cmp::Reverse,
-    collections::{BinaryHeap, HashSet, VecDeque},
-    vec,
-};
-
+use aoc_common::viz::{Frame, Visualize};
 use aoc_runner_derive::aoc;
 
-#[aoc(day10, part1)]
-pub fn part1(input: &str) -> usize {
-    let (maze, start) = parse_maze(input);
-    let height = maze.len();
-    let width = maze[0].len();
+const NORTH: usize = 0;
+const EAST: usize = 1;
+const SOUTH: usize = 2;
+const WEST: usize = 3;
+const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+const PIPES: [(u8, [usize; 2]); 6] = [
+    (b'|', [NORTH, SOUTH]),
+    (b'-', [EAST, WEST]),
+    (b'L', [NORTH, EAST]),
+    (b'J', [NORTH, WEST]),
+    (b'7', [SOUTH, WEST]),
+    (b'F', [EAST, SOUTH]),
+];
 
-    let mut costs = vec![vec![usize::MAX; width]; height];
-    let mut queue = BinaryHeap::<Reverse<(usize, (usize, usize))>>::new();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Loop,
+    Inside,
+    Outside,
+}
 
-    queue.push(Reverse((0, start)));
-    while let Some(Reverse((cost, (row, col)))) = queue.pop() {
-        if cost > costs[row][col] {
-            continue;
-        }
-        costs[row][col] = cost;
-        for &dir in &maze[row][col].0 {
-            let (dr, dc) = DIRECTIONS[dir];
-            let (new_row, new_col) = (row as isize + dr, col as isize + dc);
-            if new_row < 0 || new_row >= height as isize || new_col < 0 || new_col >= width as isize
-            {
-                continue;
-            }
-            let (new_row, new_col) = (new_row as usize, new_col as usize);
-            if maze[new_row][new_col]
-                .0
-                .iter()
-                .any(|&d| is_connected(d, dir))
-            {
-                queue.push(Reverse((cost + 1, (new_row, new_col))));
-            }
-        }
-    }
+#[derive(Debug, Clone)]
+pub struct PipeLoop {
+    // The maze with `S` replaced by its inferred pipe
+    grid: Vec<Vec<u8>>,
+    start: (usize, usize),
+    path: Vec<(usize, usize)>,
+}
 
-    *costs
+fn connections(pipe: u8) -> Option<[usize; 2]> {
+    PIPES
         .iter()
-        .flat_map(|row| row.iter())
-        .filter(|&&cost| cost < usize::MAX)
-        .max()
-        .unwrap()
+        .find(|&&(c, _)| c == pipe)
+        .map(|&(_, dirs)| dirs)
 }
 
-#[aoc(day10, part2)]
-pub fn part2(input: &str) -> usize {
-    let (maze, start) = parse_maze(input);
-    let (height, width) = (maze.len(), maze[0].len());
-
-    let mut answers = 0;
-    let loop_path = find_loop(&maze, start).unwrap();
-    for row in 0..height {
-        let mut inside = false;
-        for col in 0..width {
-            if !loop_path.contains(&(row, col)) {
-                answers += inside as usize;
-            } else {
-                match maze[row][col].1 {
-                    b'|' | b'J' | b'L' => inside = !inside,
-                    _ => {}
-                }
+impl PipeLoop {
+    pub fn from(input: &str) -> Self {
+        let mut grid = input
+            .lines()
+            .map(|line| line.bytes().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let start = grid
+            .iter()
+            .enumerate()
+            .find_map(|(row, line)| Some((row, line.iter().position(|&c| c == b'S')?)))
+            .expect("no start tile");
+
+        let step = |(row, col): (usize, usize), dir: usize| {
+            let (dr, dc) = DIRECTIONS[dir];
+            let (row, col) = (row.checked_add_signed(dr)?, col.checked_add_signed(dc)?);
+            (row < grid.len() && col < grid[row].len()).then_some((row, col))
+        };
+
+        // `S` connects to every neighbour that connects back to it.
+        let open = (0..4)
+            .filter(|&dir| {
+                step(start, dir).is_some_and(|(row, col)| {
+                    connections(grid[row][col]).is_some_and(|dirs| dirs.contains(&((dir + 2) % 4)))
+                })
+            })
+            .collect::<Vec<_>>();
+        let &(pipe, _) = PIPES
+            .iter()
+            .find(|(_, dirs)| dirs[..] == open[..])
+            .expect("start tile does not join exactly two pipes");
+
+        let mut path = vec![start];
+        let (mut pos, mut dir) = (start, open[0]);
+        loop {
+            pos = step(pos, dir).unwrap();
+            if pos == start {
+                break;
             }
+            path.push(pos);
+            let dirs = connections(grid[pos.0][pos.1]).unwrap();
+            dir = if dirs[0] == (dir + 2) % 4 {
+                dirs[1]
+            } else {
+                dirs[0]
+            };
         }
+        grid[start.0][start.1] = pipe;
+
+        PipeLoop { grid, start, path }
     }
-    answers
-}
 
-const NORTH: usize = 0;
-const EAST: usize = 1;
-const SOUTH: usize = 2;
-const WEST: usize = 3;
-const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+    pub fn len(&self) -> usize {
+        self.path.len()
+    }
 
-fn is_connected(a: usize, b: usize) -> bool {
-    (a + 2) % DIRECTIONS.len() == b
-}
+    pub fn is_empty(&self) -> bool {
+        self.path.is_empty()
+    }
 
-fn parse_maze(input: &str) -> (Vec<Vec<(Vec<usize>, u8)>>, (usize, usize)) {
-    let mut start = (0, 0);
-    let maze = input
-        .lines()
-        .enumerate()
-        .map(|(row, line)| {
-            line.chars()
-                .enumerate()
-                .map(|(col, c)| match c {
-                    '.' => (vec![], b'.'),
-                    '|' => (vec![NORTH, SOUTH], b'|'),
-                    '-' => (vec![WEST, EAST], b'-'),
-                    'S' => {
-                        start = (row, col);
-                        (vec![NORTH, EAST, SOUTH, WEST], b'S')
-                    }
-                    'J' => (vec![NORTH, WEST], b'J'),
-                    'L' => (vec![NORTH, EAST], b'L'),
-                    'F' => (vec![SOUTH, EAST], b'F'),
-                    '7' => (vec![SOUTH, WEST], b'7'),
-                    _ => panic!("Unknown character: {}", c),
-                })
-                .collect()
-        })
-        .collect();
-    (maze, start)
-}
+    pub fn start_pipe(&self) -> char {
+        self.grid[self.start.0][self.start.1] as char
+    }
 
-fn find_loop(
-    maze: &Vec<Vec<(Vec<usize>, u8)>>,
-    start: (usize, usize),
-) -> Option<HashSet<(usize, usize)>> {
-    let (height, width) = (maze.len(), maze[0].len());
+    pub fn farthest(&self) -> usize {
+        self.len() / 2
+    }
 
-    let mut path = HashSet::new();
+    // Shoelace gives the loop's area, and Pick's theorem (A = i + b/2 - 1)
+    // turns it into the number of whole tiles inside.
+    pub fn enclosed(&self) -> usize {
+        let twice_area = self
+            .path
+            .iter()
+            .zip(self.path.iter().cycle().skip(1))
+            .map(|(&(r1, c1), &(r2, c2))| (c1 * r2) as isize - (c2 * r1) as isize)
+            .sum::<isize>()
+            .unsigned_abs();
+        (twice_area + 2 - self.len()) / 2
+    }
 
-    let mut queue = VecDeque::new();
-    queue.push_back((start, usize::MAX));
-    while let Some((pos, prev_dir)) = queue.pop_front() {
-        if path.contains(&pos) {
-            continue;
+    // A tile is inside when a ray going west crosses the loop an odd number
+    // of times, counting pipes that lead north.
+    pub fn tiles(&self) -> Vec<Vec<Tile>> {
+        let mut tiles = self
+            .grid
+            .iter()
+            .map(|row| vec![Tile::Outside; row.len()])
+            .collect::<Vec<_>>();
+        for &(row, col) in &self.path {
+            tiles[row][col] = Tile::Loop;
         }
-        path.insert(pos);
-        let (row, col) = pos;
-        for &dir in &maze[row][col].0 {
-            if is_connected(dir, prev_dir) {
-                continue;
-            }
-            let (dr, dc) = DIRECTIONS[dir];
-            let (new_row, new_col) = (row as isize + dr, col as isize + dc);
-            if new_row < 0 || new_row >= height as isize || new_col < 0 || new_col >= width as isize
-            {
-                continue;
-            }
-            let (new_row, new_col) = (new_row as usize, new_col as usize);
-            if maze[new_row][new_col]
-                .0
-                .iter()
-                .any(|&d| is_connected(d, dir))
-            {
-                if path.contains(&(new_row, new_col)) {
-                    return Some(path);
+        for (row, line) in tiles.iter_mut().enumerate() {
+            let mut inside = false;
+            for (col, tile) in line.iter_mut().enumerate() {
+                if *tile == Tile::Loop {
+                    inside ^= matches!(self.grid[row][col], b'|' | b'L' | b'J');
+                } else if inside {
+                    *tile = Tile::Inside;
                 }
-                queue.push_back(((new_row, new_col), dir));
             }
         }
+        tiles
     }
-    None
+}
+
+impl Visualize for PipeLoop {
+    fn frame(&self) -> Frame {
+        let tiles = self.tiles();
+        Frame::from_rows(tiles.iter().enumerate().map(|(row, line)| {
+            line.iter().enumerate().map(move |(col, tile)| match tile {
+                Tile::Inside => 'I',
+                Tile::Outside => 'O',
+                Tile::Loop => {
+                    let heavy = (row, col) == self.start;
+                    match (self.grid[row][col], heavy) {
+                        (b'|', false) => '│',
+                        (b'-', false) => '─',
+                        (b'L', false) => '└',
+                        (b'J', false) => '┘',
+                        (b'7', false) => '┐',
+                        (b'F', false) => '┌',
+                        (b'|', true) => '┃',
+                        (b'-', true) => '━',
+                        (b'L', true) => '┗',
+                        (b'J', true) => '┛',
+                        (b'7', true) => '┓',
+                        (_, _) => '┏',
+                    }
+                }
+            })
+        }))
+    }
+}
+
+#[aoc(day10, part1)]
+pub fn part1(input: &str) -> usize {
+    PipeLoop::from(input).farthest()
+}
+
+#[aoc(day10, part2)]
+pub fn part2(input: &str) -> usize {
+    PipeLoop::from(input).enclosed()
 }
 
 #[cfg(test)]
@@ -211,5 +242,71 @@ mod tests {
             ....L---J.LJ.LJLJ...
         "};
         assert_eq!(part2(sample), 8);
+        let pipes = PipeLoop::from(sample);
+        let inside = pipes
+            .tiles()
+            .iter()
+            .flatten()
+            .filter(|&&t| t == Tile::Inside)
+            .count();
+        assert_eq!(inside, 8);
+    }
+
+    // `S` standing in for a `|` must still count as a crossing.
+    #[test]
+    fn test_vertical_start() {
+        let sample = indoc! {"
+            F-7
+            S.|
+            L-J
+        "};
+        assert_eq!(PipeLoop::from(sample).start_pipe(), '|');
+        assert_eq!(part2(sample), 1);
+        let tiles = PipeLoop::from(sample).tiles();
+        assert_eq!(tiles[1], [Tile::Loop, Tile::Inside, Tile::Loop]);
+    }
+
+    #[test]
+    fn test_smallest_loop() {
+        let pipe_loop = PipeLoop::from("S7\nLJ");
+        assert_eq!(pipe_loop.farthest(), 2);
+        assert_eq!(pipe_loop.enclosed(), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let pipes = PipeLoop::from(SAMPLE);
+        assert_eq!(pipes.start_pipe(), 'F');
+        assert_eq!(pipes.len(), 16);
+        assert_eq!(pipes.enclosed(), 1);
+        assert_eq!(
+            pipes.frame().to_string(),
+            indoc! {"
+                OO┌┐O
+                O┌┘│O
+                ┏┘I└┐
+                │┌──┘
+                └┘OOO
+            "}
+        );
+
+        let pipes = PipeLoop::from(indoc! {"
+            .....
+            .S-7.
+            .|.|.
+            .L-J.
+            .....
+        "});
+        assert_eq!(pipes.enclosed(), 1);
+        assert_eq!(
+            pipes.frame().to_string(),
+            indoc! {"
+                OOOOO
+                O┏─┐O
+                O│I│O
+                O└─┘O
+                OOOOO
+            "}
+        );
     }
 }
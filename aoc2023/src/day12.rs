@@ -1,83 +1,160 @@
-use std::collections::HashMap;
-
+use aoc_common::params::Overrides;
 use aoc_runner_derive::aoc;
 
-#[aoc(day12, part1)]
-pub fn part1(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let (springs, groups) = line.split_once(' ').unwrap();
-            let groups: Vec<usize> = groups.split(',').map(|s| s.parse().unwrap()).collect();
-            let springs: Vec<u8> = springs.bytes().collect();
-            let mut memo = HashMap::new();
-            count_arrangement(&springs, &groups, &mut memo)
-        })
-        .sum()
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub unfold: usize,
 }
 
-#[aoc(day12, part2)]
-pub fn part2(input: &str) -> usize {
-    input
-        .lines()
-        .map(|line| {
-            let (springs, groups) = line.split_once(' ').unwrap();
-            let mut groups: Vec<usize> = groups.split(',').map(|s| s.parse().unwrap()).collect();
-            let mut springs: Vec<u8> = springs.bytes().collect();
-            let (groups_copy, springs_copy) = (groups.clone(), springs.clone());
-            (0..4).for_each(|_| {
-                springs.push(b'?');
-                springs.extend(springs_copy.clone());
-                groups.extend(groups_copy.clone());
-            });
-            let mut memo = HashMap::new();
-            count_arrangement(&springs, &groups, &mut memo)
-        })
-        .sum()
+impl Default for Params {
+    fn default() -> Self {
+        Params { unfold: 5 }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params { unfold: 5 };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2023, "day12", "unfold", &mut params.unfold);
+        params
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    springs: Vec<u8>,
+    groups: Vec<usize>,
 }
 
-fn count_arrangement(
-    springs: &[u8],
-    groups: &[usize],
-    memo: &mut HashMap<(Vec<u8>, Vec<usize>), usize>,
-) -> usize {
-    if groups.is_empty() {
-        if !springs.contains(&b'#') {
-            return 1;
+impl Record {
+    pub fn from(line: &str) -> Self {
+        let (springs, groups) = line.split_once(' ').unwrap();
+        Record {
+            springs: springs.bytes().collect(),
+            groups: groups.split(',').map(|s| s.parse().unwrap()).collect(),
         }
-        return 0;
     }
 
-    let need_length = groups.iter().sum::<usize>() + groups.len() - 1;
-    if need_length > springs.len() {
-        return 0;
+    pub fn unfold(&self, factor: usize) -> Self {
+        let mut springs = Vec::with_capacity((self.springs.len() + 1) * factor);
+        for i in 0..factor {
+            if i > 0 {
+                springs.push(b'?');
+            }
+            springs.extend(&self.springs);
+        }
+        Record {
+            springs,
+            groups: self.groups.repeat(factor),
+        }
     }
 
-    if springs[0] == b'.' {
-        return count_arrangement(&springs[1..], groups, memo);
+    // Fills `table[i * (groups + 1) + g]` with the number of ways to place
+    // groups `g..` in springs `i..`, reusing the table's allocation.
+    fn fill(&self, table: &mut Vec<usize>) {
+        let (n, m) = (self.springs.len(), self.groups.len());
+        let width = m + 1;
+        table.clear();
+        table.resize((n + 1) * width, 0);
+        table[n * width + m] = 1;
+
+        // Length of the run of non-operational springs starting at i
+        let mut run = 0;
+        for i in (0..n).rev() {
+            let spring = self.springs[i];
+            run = if spring == b'.' { 0 } else { run + 1 };
+            for g in 0..=m {
+                let mut ways = 0;
+                if spring != b'#' {
+                    ways += table[(i + 1) * width + g];
+                }
+                if g < m && run >= self.groups[g] {
+                    let end = i + self.groups[g];
+                    if end == n {
+                        ways += table[n * width + g + 1];
+                    } else if self.springs[end] != b'#' {
+                        ways += table[(end + 1) * width + g + 1];
+                    }
+                }
+                table[i * width + g] = ways;
+            }
+        }
     }
 
-    let key = (springs.to_vec(), groups.to_vec());
-    if let Some(&result) = memo.get(&(key)) {
-        return result;
+    pub fn count(&self) -> usize {
+        let mut table = vec![];
+        self.count_with(&mut table)
     }
 
-    let mut total = 0;
-    let cur_group = groups[0];
-    let all_springs_valid = springs[0..cur_group].iter().all(|&c| c != b'.');
-    let last_char_valid = springs.len() == cur_group || springs[cur_group] != b'#';
-    if all_springs_valid && last_char_valid {
-        let max_idx = springs.len().min(cur_group + 1);
-        total += count_arrangement(&springs[max_idx..], &groups[1..], memo);
+    fn count_with(&self, table: &mut Vec<usize>) -> usize {
+        self.fill(table);
+        table[0]
     }
 
-    if springs[0] == b'?' {
-        total += count_arrangement(&springs[1..], groups, memo);
+    // Yields every arrangement as a string of `#` and `.`. The DP table prunes
+    // dead branches, so each step of the search leads to an arrangement.
+    pub fn arrangements(&self) -> impl Iterator<Item = String> + '_ {
+        let mut table = vec![];
+        self.fill(&mut table);
+        let (n, width) = (self.springs.len(), self.groups.len() + 1);
+        let ways = move |i: usize, g: usize| table[i * width + g];
+
+        let mut stack = vec![];
+        if ways(0, 0) > 0 {
+            stack.push((0, 0, String::new()));
+        }
+        std::iter::from_fn(move || {
+            while let Some((i, g, built)) = stack.pop() {
+                if i == n {
+                    return Some(built);
+                }
+                let spring = self.springs[i];
+                if spring != b'#' && ways(i + 1, g) > 0 {
+                    stack.push((i + 1, g, built.clone() + "."));
+                }
+                if spring != b'.' && g < self.groups.len() {
+                    let end = i + self.groups[g];
+                    let fits = end <= n
+                        && !self.springs[i..end].contains(&b'.')
+                        && self.springs.get(end) != Some(&b'#');
+                    let next = n.min(end + 1);
+                    if fits && ways(next, g + 1) > 0 {
+                        let mut built = built + &"#".repeat(self.groups[g]);
+                        if end < n {
+                            built.push('.');
+                        }
+                        stack.push((next, g + 1, built));
+                    }
+                }
+            }
+            None
+        })
     }
+}
 
-    memo.insert(key, total);
+pub fn total(input: &str, unfold: usize) -> usize {
+    let mut table = vec![];
+    input
+        .lines()
+        .map(|line| Record::from(line).unfold(unfold).count_with(&mut table))
+        .sum()
+}
+
+#[aoc(day12, part1)]
+pub fn part1(input: &str) -> usize {
+    total(input, 1)
+}
 
-    total
+pub fn part2_with(input: &str, params: &Params) -> usize {
+    total(input, params.unfold)
+}
+
+#[aoc(day12, part2)]
+pub fn part2(input: &str) -> usize {
+    part2_with(input, &Params::from_env())
 }
 
 #[cfg(test)]
@@ -102,6 +179,76 @@ mod tests {
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(SAMPLE), 525152);
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), 525152);
+    }
+
+    fn brute_force_line(line: &str) -> Vec<String> {
+        let (springs, groups) = line.split_once(' ').unwrap();
+        let unknown = springs.bytes().filter(|&c| c == b'?').count();
+        (0..1usize << unknown)
+            .map(|mask| {
+                let mut bit = 0;
+                springs
+                    .bytes()
+                    .map(|c| match c {
+                        b'?' => {
+                            bit += 1;
+                            if mask & (1 << (bit - 1)) != 0 {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        }
+                        c => c as char,
+                    })
+                    .collect::<String>()
+            })
+            .filter(|filled| {
+                let lengths = filled
+                    .split('.')
+                    .filter(|group| !group.is_empty())
+                    .map(|group| group.len().to_string())
+                    .collect::<Vec<_>>();
+                lengths.join(",") == groups
+            })
+            .collect()
+    }
+
+    fn brute_force(input: &str) -> usize {
+        input.lines().map(|line| brute_force_line(line).len()).sum()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = aoc_common::rng::Rng::new(12);
+        for size in [1, 5, 20] {
+            let input = crate::gen::day12(&mut rng, size);
+            assert_eq!(part1(&input), brute_force(&input), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let mut rng = aoc_common::rng::Rng::new(44);
+        let input = crate::gen::day12(&mut rng, 30);
+        for line in SAMPLE.lines().chain(input.lines()) {
+            let record = Record::from(line);
+            let mut arrangements = record.arrangements().collect::<Vec<_>>();
+            assert_eq!(arrangements.len(), record.count(), "{line}");
+            arrangements.sort();
+            let mut expected = brute_force_line(line);
+            expected.sort();
+            assert_eq!(arrangements, expected, "{line}");
+        }
+        let first = Record::from("???.### 1,1,3");
+        assert_eq!(first.arrangements().collect::<Vec<_>>(), ["#.#.###"]);
+    }
+
+    #[test]
+    fn test_unfold() {
+        let record = Record::from(".# 1");
+        assert_eq!(record.unfold(3), Record::from(".#?.#?.# 1,1,1"));
+        assert_eq!(part2_with(SAMPLE, &Params { unfold: 1 }), 21);
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), 525152);
     }
 }
@@ -1,129 +1,189 @@
+use std::{collections::HashMap, fmt::Display};
+
 use aoc_runner_derive::aoc;
 
-fn go_north(grid: &mut Vec<Vec<char>>, (height, width): (usize, usize)) {
-    for col in 0..width {
-        let mut empty_row = 0;
-        for row in 0..height {
-            if grid[row][col] == '#' {
-                empty_row = row + 1;
-            } else if grid[row][col] == 'O' {
-                if empty_row != row {
-                    grid[empty_row][col] = 'O';
-                    grid[row][col] = '.';
-                }
-                empty_row += 1;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tilt {
+    North,
+    West,
+    South,
+    East,
+}
+
+// Rounded rocks are kept as one bitset per column (bit i = row i), so only
+// east and west tilts need a transpose. Cube rocks are kept per row (bit i =
+// column i) and otherwise only matter through the segments of free cells
+// between them, stored as masks per row and per column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dish {
+    height: usize,
+    width: usize,
+    rounded: Vec<u128>,
+    cubes: Vec<u128>,
+    row_segments: Vec<Vec<u128>>,
+    col_segments: Vec<Vec<u128>>,
+}
+
+// The lowest `n` bits, for 1 <= n <= 128.
+fn ones(n: u32) -> u128 {
+    u128::MAX >> (128 - n)
+}
+
+fn segments(cubes: u128, len: usize) -> Vec<u128> {
+    let mut segments = vec![];
+    let mut start = 0;
+    for i in 0..=len {
+        if i == len || cubes & (1 << i) != 0 {
+            if i > start {
+                segments.push(ones((i - start) as u32) << start);
             }
+            start = i + 1;
         }
     }
+    segments
 }
 
-fn go_east(grid: &mut Vec<Vec<char>>, (height, width): (usize, usize)) {
-    for row in 0..height {
-        let mut empty_col = width - 1;
-        for col in (0..width).rev() {
-            if grid[row][col] == '#' {
-                if col > 0 {
-                    empty_col = col - 1;
-                }
-            } else if grid[row][col] == 'O' {
-                if empty_col != col {
-                    grid[row][empty_col] = 'O';
-                    grid[row][col] = '.';
-                }
-                if empty_col > 0 {
-                    empty_col -= 1;
-                }
+// Moves every rock in a line to the low or high end of its segment.
+fn settle(lines: &mut [u128], segments: &[Vec<u128>], toward_low: bool) {
+    for (line, segments) in lines.iter_mut().zip(segments) {
+        let mut settled = 0;
+        for &segment in segments {
+            let count = (*line & segment).count_ones();
+            if count == 0 {
+                continue;
             }
+            let fill = ones(count);
+            settled |= if toward_low {
+                fill << segment.trailing_zeros()
+            } else {
+                fill << (128 - segment.leading_zeros() - count)
+            };
         }
+        *line = settled;
     }
 }
 
-fn go_south(grid: &mut Vec<Vec<char>>, (height, width): (usize, usize)) {
-    for col in 0..width {
-        let mut empty_row = height - 1;
-        for row in (0..height).rev() {
-            if grid[row][col] == '#' {
-                if row > 0 {
-                    empty_row = row - 1;
-                }
-            } else if grid[row][col] == 'O' {
-                if empty_row != row {
-                    grid[empty_row][col] = 'O';
-                    grid[row][col] = '.';
-                }
-                if empty_row > 0 {
-                    empty_row -= 1;
-                }
-            }
+fn transpose(lines: &[u128], len: usize) -> Vec<u128> {
+    let mut transposed = vec![0; len];
+    for (j, &line) in lines.iter().enumerate() {
+        let mut bits = line;
+        while bits != 0 {
+            transposed[bits.trailing_zeros() as usize] |= 1 << j;
+            bits &= bits - 1;
         }
     }
+    transposed
 }
 
-fn go_west(grid: &mut Vec<Vec<char>>, (height, width): (usize, usize)) {
-    for row in 0..height {
-        let mut empty_col = 0;
-        for col in 0..width {
-            if grid[row][col] == '#' {
-                empty_col = col + 1;
-            } else if grid[row][col] == 'O' {
-                if empty_col != col {
-                    grid[row][empty_col] = 'O';
-                    grid[row][col] = '.';
-                }
-                empty_col += 1;
+impl Dish {
+    pub fn from(input: &str) -> Self {
+        let lines = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (height, width) = (lines.len(), lines[0].len());
+        assert!(
+            height <= 128 && width <= 128,
+            "dish is larger than 128 cells"
+        );
+
+        let row_bits = |c: u8| {
+            lines
+                .iter()
+                .map(|line| {
+                    line.iter()
+                        .enumerate()
+                        .filter(|&(_, &b)| b == c)
+                        .fold(0u128, |acc, (i, _)| acc | 1 << i)
+                })
+                .collect::<Vec<_>>()
+        };
+        let (rounded, cubes) = (row_bits(b'O'), row_bits(b'#'));
+        Dish {
+            height,
+            width,
+            row_segments: cubes.iter().map(|&c| segments(c, width)).collect(),
+            col_segments: transpose(&cubes, width)
+                .iter()
+                .map(|&c| segments(c, height))
+                .collect(),
+            rounded: transpose(&rounded, width),
+            cubes,
+        }
+    }
+
+    pub fn tilt(&mut self, tilt: Tilt) {
+        match tilt {
+            Tilt::North | Tilt::South => {
+                settle(&mut self.rounded, &self.col_segments, tilt == Tilt::North);
+            }
+            Tilt::West | Tilt::East => {
+                let mut rows = transpose(&self.rounded, self.height);
+                settle(&mut rows, &self.row_segments, tilt == Tilt::West);
+                self.rounded = transpose(&rows, self.width);
             }
         }
     }
+
+    pub fn spin(&mut self) {
+        for tilt in [Tilt::North, Tilt::West, Tilt::South, Tilt::East] {
+            self.tilt(tilt);
+        }
+    }
+
+    pub fn load(&self) -> usize {
+        let mut load = 0;
+        for &column in &self.rounded {
+            let mut bits = column;
+            while bits != 0 {
+                load += self.height - bits.trailing_zeros() as usize;
+                bits &= bits - 1;
+            }
+        }
+        load
+    }
+}
+
+impl Display for Dish {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rows = transpose(&self.rounded, self.height);
+        for (rounded, cubes) in rows.iter().zip(&self.cubes) {
+            let line = (0..self.width)
+                .map(|i| match (rounded >> i & 1, cubes >> i & 1) {
+                    (1, _) => 'O',
+                    (_, 1) => '#',
+                    _ => '.',
+                })
+                .collect::<String>();
+            writeln!(f, "{line}")?;
+        }
+        Ok(())
+    }
 }
 
-fn cal_load(grid: &Vec<Vec<char>>, (height, width): (usize, usize)) -> usize {
-    (0..height)
-        .flat_map(|row| (0..width).map(move |col| (row, col)))
-        .filter(|&(row, col)| grid[row][col] == 'O')
-        .map(|(row, _)| height - row)
-        .sum()
+// Spins until a state repeats, then skips the remaining whole cycles.
+pub fn load_after_spins(input: &str, spins: usize) -> usize {
+    let mut dish = Dish::from(input);
+    let mut seen = HashMap::new();
+    let mut loads = vec![dish.load()];
+    for spin in 1..=spins {
+        seen.insert(dish.rounded.clone(), spin - 1);
+        dish.spin();
+        loads.push(dish.load());
+        if let Some(&start) = seen.get(&dish.rounded) {
+            return loads[start + (spins - start) % (spin - start)];
+        }
+    }
+    dish.load()
 }
 
 #[aoc(day14, part1)]
 pub fn part1(input: &str) -> usize {
-    let mut grid = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let (height, width) = (grid.len(), grid[0].len());
-
-    go_north(&mut grid, (height, width));
-    cal_load(&grid, (height, width))
+    let mut dish = Dish::from(input);
+    dish.tilt(Tilt::North);
+    dish.load()
 }
 
 #[aoc(day14, part2)]
 pub fn part2(input: &str) -> usize {
-    let mut grid = input
-        .lines()
-        .map(|line| line.chars().collect::<Vec<_>>())
-        .collect::<Vec<_>>();
-
-    let (height, width) = (grid.len(), grid[0].len());
-
-    let mut seen = vec![];
-    loop {
-        go_north(&mut grid, (height, width));
-        go_west(&mut grid, (height, width));
-        go_south(&mut grid, (height, width));
-        go_east(&mut grid, (height, width));
-
-        if seen.contains(&grid) {
-            break;
-        }
-        seen.push(grid.clone());
-    }
-
-    let first = seen.iter().position(|g| g == &grid).unwrap();
-    cal_load(
-        &seen[(1000000000 - first) % (seen.len() - first) + first - 1],
-        (height, width),
-    )
+    load_after_spins(input, 1000000000)
 }
 
 #[cfg(test)]
@@ -131,6 +191,8 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
+    use aoc_common::rng::Rng;
 
     const SAMPLE: &str = indoc! {"
         O....#....
@@ -154,4 +216,81 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 64);
     }
+
+    #[test]
+    fn test_spin() {
+        let mut dish = Dish::from(SAMPLE);
+        dish.spin();
+        assert_eq!(
+            dish.to_string(),
+            indoc! {"
+                .....#....
+                ....#...O#
+                ...OO##...
+                .OO#......
+                .....OOO#.
+                .O#...O#.#
+                ....O#....
+                ......OOOO
+                #...O###..
+                #..OO#....
+            "}
+        );
+        dish.tilt(Tilt::West);
+        assert_eq!(dish.to_string().lines().nth(2), Some("OO...##..."));
+    }
+
+    #[test]
+    fn test_against_plain_spins() {
+        let mut dish = Dish::from(SAMPLE);
+        for spins in 0..30 {
+            assert_eq!(load_after_spins(SAMPLE, spins), dish.load());
+            dish.spin();
+        }
+    }
+
+    #[test]
+    fn test_wide_dish() {
+        let input = format!("{}#O\n{}..", "O.".repeat(60), ".".repeat(120));
+        let mut dish = Dish::from(&input);
+        dish.tilt(Tilt::East);
+        assert_eq!(
+            dish.to_string().lines().next().unwrap()[60..],
+            format!("{}#O", "O".repeat(60))
+        );
+        dish.tilt(Tilt::South);
+        assert_eq!(dish.load(), 61);
+    }
+
+    #[test]
+    fn test_full_width_dish() {
+        let row = format!("O{}", ".".repeat(127));
+        let mut dish = Dish::from(&row);
+        dish.tilt(Tilt::East);
+        assert_eq!(
+            dish.to_string().trim_end(),
+            row.chars().rev().collect::<String>()
+        );
+        dish.tilt(Tilt::West);
+        assert_eq!(dish.to_string().trim_end(), row);
+
+        let column = format!("{}O", ".\n".repeat(127));
+        let mut dish = Dish::from(&column);
+        assert_eq!(dish.load(), 1);
+        dish.tilt(Tilt::North);
+        assert_eq!(dish.load(), 128);
+    }
+
+    #[test]
+    fn test_generated_against_plain_spins() {
+        let mut rng = Rng::new(14);
+        for size in [3, 8, 20] {
+            let input = gen::day14(&mut rng, size);
+            let mut dish = Dish::from(&input);
+            for spins in 0..60 {
+                assert_eq!(load_after_spins(&input, spins), dish.load(), "{input}");
+                dish.spin();
+            }
+        }
+    }
 }
@@ -1,7 +1,13 @@
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Display},
+    hash::Hash,
+};
+
 use aoc_runner_derive::aoc;
 
-fn hash(s: &str) -> usize {
-    s.as_bytes().iter().fold(0, |mut hash, &byte| {
+pub fn hash(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |mut hash, &byte| {
         hash += byte as usize;
         hash *= 17;
         hash %= 256;
@@ -9,42 +15,163 @@ fn hash(s: &str) -> usize {
     })
 }
 
-#[aoc(day15, part1)]
-pub fn part1(input: &str) -> usize {
-    input.split(",").map(|s| hash(s)).sum()
+const BOXES: usize = 256;
+
+// Removed lenses leave a hole in their box so the index of every other lens
+// stays valid; a box is compacted once most of its slots are holes.
+#[derive(Clone)]
+struct LensBox<K, V> {
+    slots: Vec<Option<(K, V)>>,
+    live: usize,
 }
 
-#[aoc(day15, part2)]
-pub fn part2(input: &str) -> usize {
-    let map = input.split(",").fold(vec![vec![]; 256], |mut map, s| {
-        let (key, value) = s.split_once(['=', '-']).unwrap();
-        let values = &mut map[hash(key)];
-        if value.len() == 0 {
-            if let Some(pos) = values.iter().position(|&(k, _)| k == key) {
-                values.remove(pos);
-            }
-        } else {
-            if let Some(slot) = values.iter_mut().find(|(k, _)| *k == key) {
-                slot.1 = value;
-            } else {
-                values.push((key, value));
+#[derive(Clone)]
+pub struct LensMap<K, V> {
+    boxes: Vec<LensBox<K, V>>,
+    index: HashMap<K, (usize, usize)>,
+}
+
+impl<K, V> Default for LensMap<K, V> {
+    fn default() -> Self {
+        LensMap {
+            boxes: (0..BOXES)
+                .map(|_| LensBox {
+                    slots: vec![],
+                    live: 0,
+                })
+                .collect(),
+            index: HashMap::new(),
+        }
+    }
+}
+
+impl<K: AsRef<[u8]> + Hash + Eq + Clone, V> LensMap<K, V> {
+    pub fn new() -> Self {
+        LensMap::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let &(b, slot) = self.index.get(key)?;
+        self.boxes[b].slots[slot].as_ref().map(|(_, value)| value)
+    }
+
+    // Replacing a lens keeps its slot; a new lens goes behind the others.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&(b, slot)) = self.index.get(&key) {
+            let (_, old) = self.boxes[b].slots[slot].as_mut().unwrap();
+            return Some(std::mem::replace(old, value));
+        }
+        let b = hash(key.as_ref());
+        let lens_box = &mut self.boxes[b];
+        self.index.insert(key.clone(), (b, lens_box.slots.len()));
+        lens_box.slots.push(Some((key, value)));
+        lens_box.live += 1;
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (b, slot) = self.index.remove(key)?;
+        let lens_box = &mut self.boxes[b];
+        let (_, value) = lens_box.slots[slot].take().unwrap();
+        lens_box.live -= 1;
+        while lens_box.slots.last().is_some_and(Option::is_none) {
+            lens_box.slots.pop();
+        }
+        if lens_box.live * 2 < lens_box.slots.len() {
+            lens_box.slots.retain(Option::is_some);
+            for (slot, (key, _)) in lens_box.slots.iter().flatten().enumerate() {
+                self.index.insert(key.clone(), (b, slot));
             }
         }
-        map
-    });
+        Some(value)
+    }
+}
 
-    map.iter()
-        .enumerate()
-        .map(|(box_index, slots)| {
-            slots
+impl<K, V> LensMap<K, V> {
+    // Yields (box, slot, key, value) with slots counted from 0 among the
+    // lenses currently in the box.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, &K, &V)> {
+        self.boxes.iter().enumerate().flat_map(|(b, lens_box)| {
+            lens_box
+                .slots
                 .iter()
+                .flatten()
                 .enumerate()
-                .map(|(slot_index, (_, value))| {
-                    (box_index + 1) * (slot_index + 1) * value.parse::<usize>().unwrap()
-                })
-                .sum::<usize>()
+                .map(move |(slot, (key, value))| (b, slot, key, value))
+        })
+    }
+
+    pub fn focusing_power(&self) -> usize
+    where
+        V: Copy + Into<usize>,
+    {
+        self.iter()
+            .map(|(b, slot, _, &value)| (b + 1) * (slot + 1) * value.into())
+            .sum()
+    }
+}
+
+// Matches the box listings in the puzzle's example.
+impl<K: Display, V: Display> Debug for LensMap<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (b, lens_box) in self.boxes.iter().enumerate() {
+            if lens_box.live == 0 {
+                continue;
+            }
+            write!(f, "Box {b}:")?;
+            for (key, value) in lens_box.slots.iter().flatten() {
+                write!(f, " [{key} {value}]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn apply<'a>(map: &mut LensMap<&'a str, u8>, step: &'a str) {
+    match step.split_once('=') {
+        Some((label, focal_length)) => {
+            map.insert(label, focal_length.parse().unwrap());
+        }
+        None => {
+            map.remove(&step.trim_end_matches('-'));
+        }
+    }
+}
+
+pub fn trace(input: &str) -> String {
+    let mut map = LensMap::new();
+    input
+        .trim()
+        .split(',')
+        .map(|step| {
+            apply(&mut map, step);
+            format!("After \"{step}\":\n{map:?}")
         })
-        .sum()
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[aoc(day15, part1)]
+pub fn part1(input: &str) -> usize {
+    input.split(",").map(|s| hash(s.as_bytes())).sum()
+}
+
+#[aoc(day15, part2)]
+pub fn part2(input: &str) -> usize {
+    let mut map = LensMap::new();
+    for step in input.split(',') {
+        apply(&mut map, step);
+    }
+    map.focusing_power()
 }
 
 #[cfg(test)]
@@ -64,4 +191,65 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 145);
     }
+
+    #[test]
+    fn test_trace() {
+        let trace = trace(SAMPLE);
+        assert!(trace.starts_with(indoc! {r#"
+            After "rn=1":
+            Box 0: [rn 1]
+
+            After "cm-":
+            Box 0: [rn 1]
+
+            After "qp=3":
+            Box 0: [rn 1]
+            Box 1: [qp 3]
+        "#}));
+        assert!(trace.ends_with(indoc! {r#"
+            After "ot=7":
+            Box 0: [rn 1] [cm 2]
+            Box 3: [ot 7] [ab 5] [pc 6]
+        "#}));
+    }
+
+    #[test]
+    fn test_against_boxes_of_vecs() {
+        let mut rng = aoc_common::rng::Rng::new(15);
+        let input = crate::gen::day15(&mut rng, 2000);
+        let mut map = LensMap::new();
+        let mut boxes = vec![vec![]; 256];
+        for step in input.split(',') {
+            apply(&mut map, step);
+            let (label, focal_length) = step.split_once(['=', '-']).unwrap();
+            let lenses = &mut boxes[hash(label.as_bytes())];
+            let slot = lenses.iter().position(|&(l, _)| l == label);
+            match (slot, focal_length.parse::<u8>()) {
+                (Some(slot), Ok(f)) => lenses[slot] = (label, f),
+                (None, Ok(f)) => lenses.push((label, f)),
+                (Some(slot), Err(_)) => {
+                    lenses.remove(slot);
+                }
+                (None, Err(_)) => {}
+            }
+        }
+
+        let expected = boxes
+            .iter()
+            .enumerate()
+            .flat_map(|(b, lenses)| {
+                lenses
+                    .iter()
+                    .enumerate()
+                    .map(move |(slot, &(label, f))| (b, slot, label, f))
+            })
+            .collect::<Vec<_>>();
+        let actual = map
+            .iter()
+            .map(|(b, slot, &label, &f)| (b, slot, label, f))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, expected);
+        assert_eq!(map.len(), expected.len());
+        assert_eq!(map.get(&expected[0].2), Some(&expected[0].3));
+    }
 }
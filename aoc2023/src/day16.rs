@@ -1,5 +1,6 @@
-use std::{usize, vec};
+use std::{collections::HashMap, thread};
 
+use aoc_common::{bitset::BitSet, viz::Frame};
 use aoc_runner_derive::aoc;
 
 const NORTH: usize = 0;
@@ -8,87 +9,234 @@ const SOUTH: usize = 2;
 const WEST: usize = 3;
 const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
 
-fn energized_tiles(grid: &Vec<Vec<char>>, (r, c, d): (usize, usize, usize)) -> usize {
-    let mut seen = vec![vec![[false; 4]; grid[0].len()]; grid.len()];
-    let mut queue = vec![(r, c, d)];
-    while let Some((r, c, d)) = queue.pop() {
-        if seen[r][c][d] {
-            continue;
-        }
-        seen[r][c][d] = true;
+// A beam entering (row, col) heading in a direction
+pub type Entry = (usize, usize, usize);
+
+fn outputs(tile: u8, d: usize) -> &'static [usize] {
+    match (tile, d) {
+        (b'|', EAST | WEST) => &[NORTH, SOUTH],
+        (b'-', NORTH | SOUTH) => &[WEST, EAST],
+        (b'\\', NORTH) | (b'/', SOUTH) => &[WEST],
+        (b'\\', EAST) | (b'/', WEST) => &[SOUTH],
+        (b'\\', SOUTH) | (b'/', NORTH) => &[EAST],
+        (b'\\', WEST) | (b'/', EAST) => &[NORTH],
+        (_, NORTH) => &[NORTH],
+        (_, EAST) => &[EAST],
+        (_, SOUTH) => &[SOUTH],
+        _ => &[WEST],
+    }
+}
+
+// Every node is a straight beam segment running from its entry up to the next
+// tile that turns or splits it. The nodes are grouped into strongly connected
+// components, and each component's tiles are collected once.
+pub struct BeamGraph {
+    height: usize,
+    width: usize,
+    nodes: HashMap<Entry, usize>,
+    component: Vec<usize>,
+    component_tiles: Vec<BitSet>,
+    component_edges: Vec<Vec<usize>>,
+}
 
-        let next_directions = match grid[r][c] {
-            '.' => {
-                vec![d]
+impl BeamGraph {
+    pub fn from(input: &str) -> Self {
+        let grid = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let (height, width) = (grid.len(), grid[0].len());
+        let step = |(r, c): (usize, usize), d: usize| {
+            let (dr, dc) = DIRECTIONS[d];
+            let (r, c) = (r.checked_add_signed(dr)?, c.checked_add_signed(dc)?);
+            (r < height && c < width).then_some((r, c))
+        };
+
+        let mut nodes = HashMap::new();
+        let mut tiles: Vec<Vec<usize>> = vec![];
+        let mut edges: Vec<Vec<usize>> = vec![];
+        let mut pending = edge_entries(height, width);
+        for &entry in &pending {
+            nodes.insert(entry, nodes.len());
+        }
+        while let Some(entry) = pending.pop() {
+            let id = nodes[&entry];
+            if tiles.len() <= id {
+                tiles.resize(id + 1, vec![]);
+                edges.resize(id + 1, vec![]);
             }
-            '|' => {
-                if d == NORTH || d == SOUTH {
-                    vec![d]
-                } else {
-                    vec![NORTH, SOUTH]
+            let (mut pos, d) = ((entry.0, entry.1), entry.2);
+            loop {
+                tiles[id].push(pos.0 * width + pos.1);
+                let outs = outputs(grid[pos.0][pos.1], d);
+                if outs != [d] {
+                    for &out in outs {
+                        if let Some((r, c)) = step(pos, out) {
+                            let next = (r, c, out);
+                            let len = nodes.len();
+                            let next_id = *nodes.entry(next).or_insert_with(|| {
+                                pending.push(next);
+                                len
+                            });
+                            edges[id].push(next_id);
+                        }
+                    }
+                    break;
+                }
+                match step(pos, d) {
+                    Some(next) => pos = next,
+                    None => break,
                 }
             }
-            '-' => {
-                if d == WEST || d == EAST {
-                    vec![d]
-                } else {
-                    vec![WEST, EAST]
+        }
+
+        let component = strongly_connected(&edges);
+        let count = component.iter().max().map_or(0, |&c| c + 1);
+        let mut component_tiles = vec![BitSet::new(); count];
+        let mut component_edges = vec![vec![]; count];
+        for (id, &c) in component.iter().enumerate() {
+            component_tiles[c].union_with(&tiles[id].iter().copied().collect());
+            for &next in &edges[id] {
+                if component[next] != c && !component_edges[c].contains(&component[next]) {
+                    component_edges[c].push(component[next]);
                 }
             }
-            '\\' => match d {
-                NORTH => vec![WEST],
-                EAST => vec![SOUTH],
-                SOUTH => vec![EAST],
-                WEST => vec![NORTH],
-                _ => unreachable!(),
-            },
-            '/' => match d {
-                NORTH => vec![EAST],
-                EAST => vec![NORTH],
-                SOUTH => vec![WEST],
-                WEST => vec![SOUTH],
-                _ => unreachable!(),
-            },
-            _ => unreachable!(),
-        };
+        }
 
-        for nd in next_directions {
-            let (dr, dc) = DIRECTIONS[nd];
-            let (nr, nc) = (r as isize + dr, c as isize + dc);
-            if nr >= 0 && nc >= 0 && nr < grid.len() as isize && nc < grid[0].len() as isize {
-                queue.push((nr as usize, nc as usize, nd));
+        BeamGraph {
+            height,
+            width,
+            nodes,
+            component,
+            component_tiles,
+            component_edges,
+        }
+    }
+
+    pub fn energized(&self, entry: Entry) -> BitSet {
+        let start = self.component[self.nodes[&entry]];
+        let mut seen = vec![false; self.component_tiles.len()];
+        let mut stack = vec![start];
+        seen[start] = true;
+        let mut energized = BitSet::new();
+        while let Some(c) = stack.pop() {
+            energized.union_with(&self.component_tiles[c]);
+            for &next in &self.component_edges[c] {
+                if !seen[next] {
+                    seen[next] = true;
+                    stack.push(next);
+                }
             }
         }
+        energized
     }
 
-    seen.iter()
-        .flatten()
-        .filter(|ds| ds.iter().any(|&d| d))
-        .count()
+    pub fn render(&self, entry: Entry) -> Frame {
+        let energized = self.energized(entry);
+        let mut frame = Frame::new(self.width, self.height);
+        for tile in energized.iter() {
+            frame.set(tile % self.width, tile / self.width, '#');
+        }
+        frame
+    }
+
+    // Splits the edge entries between threads; the graph itself is shared.
+    pub fn max_energized(&self) -> usize {
+        let entries = edge_entries(self.height, self.width);
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = entries.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let handles = entries
+                .chunks(chunk)
+                .map(|entries| {
+                    scope.spawn(move || {
+                        entries
+                            .iter()
+                            .map(|&entry| self.energized(entry).len())
+                            .max()
+                            .unwrap_or(0)
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .max()
+                .unwrap_or(0)
+        })
+    }
+}
+
+fn edge_entries(height: usize, width: usize) -> Vec<Entry> {
+    let mut entries = vec![];
+    for c in 0..width {
+        entries.push((0, c, SOUTH));
+        entries.push((height - 1, c, NORTH));
+    }
+    for r in 0..height {
+        entries.push((r, 0, EAST));
+        entries.push((r, width - 1, WEST));
+    }
+    entries
+}
+
+// Tarjan's algorithm, iterative so long beam chains can't overflow the stack.
+// Returns the component of every node.
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<usize> {
+    const UNSEEN: usize = usize::MAX;
+    let n = edges.len();
+    let (mut index, mut low) = (vec![UNSEEN; n], vec![0; n]);
+    let mut component = vec![UNSEEN; n];
+    let (mut on_stack, mut stack) = (vec![false; n], vec![]);
+    let (mut next_index, mut count) = (0, 0);
+
+    for root in 0..n {
+        if index[root] != UNSEEN {
+            continue;
+        }
+        let mut calls = vec![(root, 0)];
+        while let Some(&(v, i)) = calls.last() {
+            if i == 0 {
+                index[v] = next_index;
+                low[v] = next_index;
+                next_index += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = edges[v].get(i) {
+                calls.last_mut().unwrap().1 += 1;
+                if index[w] == UNSEEN {
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(u, _)) = calls.last() {
+                low[u] = low[u].min(low[v]);
+            }
+            if low[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = count;
+                    if w == v {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    component
 }
 
 #[aoc(day16, part1)]
 pub fn part1(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
-    energized_tiles(&grid, (0, 0, EAST))
+    BeamGraph::from(input).energized((0, 0, EAST)).len()
 }
 
 #[aoc(day16, part2)]
 pub fn part2(input: &str) -> usize {
-    let grid: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
-
-    let mut max_energized = 0;
-    for c in 0..grid[0].len() {
-        max_energized = energized_tiles(&grid, (0, c, SOUTH)).max(max_energized);
-        max_energized = energized_tiles(&grid, (grid.len() - 1, c, NORTH)).max(max_energized);
-    }
-    for r in 0..grid.len() {
-        max_energized = energized_tiles(&grid, (r, 0, EAST)).max(max_energized);
-        max_energized = energized_tiles(&grid, (r, grid[0].len() - 1, WEST)).max(max_energized);
-    }
-
-    max_energized
+    BeamGraph::from(input).max_energized()
 }
 
 #[cfg(test)]
@@ -119,4 +267,66 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 51);
     }
+
+    #[test]
+    fn test_render() {
+        let graph = BeamGraph::from(SAMPLE);
+        assert_eq!(
+            graph.render((0, 0, EAST)).to_string(),
+            indoc! {"
+                ######....
+                .#...#....
+                .#...#####
+                .#...##...
+                .#...##...
+                .#...##...
+                .#..####..
+                ########..
+                .#######..
+                .#...#.#..
+            "}
+        );
+    }
+
+    fn trace(input: &str, (r, c, d): Entry) -> usize {
+        let grid = input.lines().map(str::as_bytes).collect::<Vec<_>>();
+        let mut seen = vec![vec![[false; 4]; grid[0].len()]; grid.len()];
+        let mut queue = vec![(r, c, d)];
+        while let Some((r, c, d)) = queue.pop() {
+            if seen[r][c][d] {
+                continue;
+            }
+            seen[r][c][d] = true;
+            for &nd in outputs(grid[r][c], d) {
+                let (dr, dc) = DIRECTIONS[nd];
+                let (nr, nc) = (r as isize + dr, c as isize + dc);
+                if nr >= 0 && nc >= 0 && nr < grid.len() as isize && nc < grid[0].len() as isize {
+                    queue.push((nr as usize, nc as usize, nd));
+                }
+            }
+        }
+        seen.iter()
+            .flatten()
+            .filter(|ds| ds.iter().any(|&d| d))
+            .count()
+    }
+
+    #[test]
+    fn test_against_tracing() {
+        let mut rng = aoc_common::rng::Rng::new(16);
+        for size in [1, 3, 8, 20] {
+            let input = crate::gen::day16(&mut rng, size);
+            let graph = BeamGraph::from(&input);
+            let entries = edge_entries(size, size);
+            for &entry in &entries {
+                assert_eq!(
+                    graph.energized(entry).len(),
+                    trace(&input, entry),
+                    "{input}"
+                );
+            }
+            let best = entries.iter().map(|&e| trace(&input, e)).max().unwrap();
+            assert_eq!(graph.max_energized(), best);
+        }
+    }
 }
@@ -1,148 +1,335 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Display, str::FromStr};
 
 use aoc_runner_derive::aoc;
 
-fn accept(workflows: &HashMap<&str, Vec<&str>>, input: &str, states: &Vec<usize>) -> bool {
-    if input == "A" || input == "R" {
-        return input == "A";
-    }
-    for rule in workflows.get(input).unwrap().iter() {
-        if let Some((rule, target)) = rule.split_once(':') {
-            let (left, op, right) = (
-                &rule[0..1],
-                &rule[1..2],
-                rule[2..].parse::<usize>().unwrap(),
-            );
-            let left = match left {
-                "x" => states[0],
-                "m" => states[1],
-                "a" => states[2],
-                "s" => states[3],
-                _ => unreachable!(),
-            };
-            match op {
-                "<" => {
-                    if left < right {
-                        return accept(workflows, target, states);
-                    }
-                }
-                ">" => {
-                    if left > right {
-                        return accept(workflows, target, states);
-                    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    X,
+    M,
+    A,
+    S,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Lt,
+    Gt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Accept,
+    Reject,
+    Workflow(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub category: Category,
+    pub cmp: Cmp,
+    pub value: u64,
+    pub target: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflow {
+    pub name: String,
+    pub rules: Vec<Rule>,
+    pub fallback: Target,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workflows {
+    workflows: Vec<Workflow>,
+    start: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WorkflowError {
+    Syntax(String),
+    UnknownTarget { workflow: String, target: String },
+    MissingStart,
+    Cycle(Vec<String>),
+}
+
+impl Display for WorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WorkflowError::Syntax(line) => write!(f, "cannot parse `{line}`"),
+            WorkflowError::UnknownTarget { workflow, target } => {
+                write!(f, "workflow `{workflow}` sends parts to unknown `{target}`")
+            }
+            WorkflowError::MissingStart => write!(f, "no `in` workflow"),
+            WorkflowError::Cycle(names) => write!(f, "workflows loop: {}", names.join(" -> ")),
+        }
+    }
+}
+
+impl std::error::Error for WorkflowError {}
+
+pub type Part = [u64; 4];
+
+// Inclusive (low, high) bounds
+pub type Range = (u64, u64);
+
+// One range per category, in x, m, a, s order.
+pub type Region = [Range; 4];
+
+impl Category {
+    fn from(c: char) -> Option<Self> {
+        match c {
+            'x' => Some(Category::X),
+            'm' => Some(Category::M),
+            'a' => Some(Category::A),
+            's' => Some(Category::S),
+            _ => None,
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl Rule {
+    // Splits a range into the parts that match and don't match this rule.
+    fn split(&self, (low, high): Range) -> (Option<Range>, Option<Range>) {
+        let non_empty = |(low, high): Range| (low <= high).then_some((low, high));
+        match self.cmp {
+            Cmp::Lt => (
+                self.value
+                    .checked_sub(1)
+                    .and_then(|below| non_empty((low, high.min(below)))),
+                non_empty((low.max(self.value), high)),
+            ),
+            Cmp::Gt => (
+                non_empty((low.max(self.value + 1), high)),
+                non_empty((low, high.min(self.value))),
+            ),
+        }
+    }
+}
+
+pub fn volume(region: &Region) -> u64 {
+    region.iter().map(|(low, high)| high - low + 1).product()
+}
+
+impl FromStr for Workflows {
+    type Err = WorkflowError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let syntax = |line: &str| WorkflowError::Syntax(line.to_string());
+        let mut raw = vec![];
+        for line in s.lines() {
+            let (name, body) = line.split_once('{').ok_or_else(|| syntax(line))?;
+            let body = body.strip_suffix('}').ok_or_else(|| syntax(line))?;
+            let mut rules = body.split(',').collect::<Vec<_>>();
+            let fallback = rules.pop().filter(|f| !f.contains(':'));
+            raw.push((name, rules, fallback.ok_or_else(|| syntax(line))?, line));
+        }
+
+        let indices = raw
+            .iter()
+            .enumerate()
+            .map(|(i, (name, ..))| (*name, i))
+            .collect::<HashMap<_, _>>();
+        let target = |workflow: &str, target: &str| match target {
+            "A" => Ok(Target::Accept),
+            "R" => Ok(Target::Reject),
+            _ => indices
+                .get(target)
+                .map(|&i| Target::Workflow(i))
+                .ok_or_else(|| WorkflowError::UnknownTarget {
+                    workflow: workflow.to_string(),
+                    target: target.to_string(),
+                }),
+        };
+
+        let mut workflows = vec![];
+        for (name, rules, fallback, line) in raw.iter() {
+            let rules = rules
+                .iter()
+                .map(|rule| {
+                    let (condition, to) = rule.split_once(':').ok_or_else(|| syntax(line))?;
+                    let mut chars = condition.chars();
+                    let category = chars.next().and_then(Category::from);
+                    let cmp = match chars.next() {
+                        Some('<') => Some(Cmp::Lt),
+                        Some('>') => Some(Cmp::Gt),
+                        _ => None,
+                    };
+                    let value = chars.as_str().parse().ok();
+                    let (Some(category), Some(cmp), Some(value)) = (category, cmp, value) else {
+                        return Err(syntax(line));
+                    };
+                    Ok(Rule {
+                        category,
+                        cmp,
+                        value,
+                        target: target(name, to)?,
+                    })
+                })
+                .collect::<Result<_, _>>()?;
+            workflows.push(Workflow {
+                name: name.to_string(),
+                rules,
+                fallback: target(name, fallback)?,
+            });
+        }
+
+        let start = *indices.get("in").ok_or(WorkflowError::MissingStart)?;
+        let workflows = Workflows { workflows, start };
+        workflows.check_cycles()?;
+        Ok(workflows)
+    }
+}
+
+impl Workflows {
+    pub fn workflows(&self) -> &[Workflow] {
+        &self.workflows
+    }
+
+    fn targets(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let workflow = &self.workflows[index];
+        workflow
+            .rules
+            .iter()
+            .map(|rule| rule.target)
+            .chain([workflow.fallback])
+            .filter_map(|target| match target {
+                Target::Workflow(next) => Some(next),
+                _ => None,
+            })
+    }
+
+    fn check_cycles(&self) -> Result<(), WorkflowError> {
+        // 0 = unvisited, 1 = on the current path, 2 = done
+        let mut state = vec![0; self.workflows.len()];
+        let mut path = vec![];
+        for root in 0..self.workflows.len() {
+            if state[root] == 0 {
+                self.visit(root, &mut state, &mut path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn visit(
+        &self,
+        index: usize,
+        state: &mut [u8],
+        path: &mut Vec<usize>,
+    ) -> Result<(), WorkflowError> {
+        state[index] = 1;
+        path.push(index);
+        for next in self.targets(index) {
+            match state[next] {
+                0 => self.visit(next, state, path)?,
+                1 => {
+                    let from = path.iter().position(|&i| i == next).unwrap();
+                    let names = path[from..]
+                        .iter()
+                        .chain([&next])
+                        .map(|&i| self.workflows[i].name.clone())
+                        .collect();
+                    return Err(WorkflowError::Cycle(names));
                 }
-                _ => unreachable!(),
+                _ => {}
+            }
+        }
+        path.pop();
+        state[index] = 2;
+        Ok(())
+    }
+
+    pub fn accepts(&self, part: &Part) -> bool {
+        let mut current = Target::Workflow(self.start);
+        loop {
+            let Target::Workflow(index) = current else {
+                return current == Target::Accept;
             };
-        } else {
-            return accept(workflows, rule, states);
+            let workflow = &self.workflows[index];
+            current = workflow
+                .rules
+                .iter()
+                .find(|rule| {
+                    let value = part[rule.category.index()];
+                    match rule.cmp {
+                        Cmp::Lt => value < rule.value,
+                        Cmp::Gt => value > rule.value,
+                    }
+                })
+                .map_or(workflow.fallback, |rule| rule.target);
         }
     }
 
-    false
-}
-
-fn count(
-    workflows: &HashMap<&str, Vec<&str>>,
-    input: &str,
-    ranges: &mut Vec<(usize, usize)>,
-) -> usize {
-    if input == "R" {
-        return 0;
-    }
-    if input == "A" {
-        return ranges.iter().fold(1, |mut product, (low, high)| {
-            product *= high - low + 1;
-            product
-        });
-    }
-
-    workflows
-        .get(input)
-        .unwrap()
-        .iter()
-        .map(|rule| {
-            if let Some((rule, target)) = rule.split_once(':') {
-                let mut total = 0;
-                let (left, op, right) = (
-                    &rule[0..1],
-                    &rule[1..2],
-                    rule[2..].parse::<usize>().unwrap(),
-                );
-                let range_index = match left {
-                    "x" => 0,
-                    "m" => 1,
-                    "a" => 2,
-                    "s" => 3,
-                    _ => unreachable!(),
-                };
-                let (low, high) = ranges[range_index];
-                let matches = match op {
-                    "<" => [(low, high.min(right - 1)), (low.max(right), high)],
-                    ">" => [(low.max(right + 1), high), (low, high.min(right))],
-                    _ => unreachable!(),
-                };
-                if matches[0].0 <= matches[0].1 {
-                    let mut ranges_copy = ranges.clone();
-                    ranges_copy[range_index] = matches[0];
-                    total += count(workflows, target, &mut ranges_copy)
+    // The accepted parts of `region` as disjoint regions.
+    pub fn accepted(&self, region: Region) -> Vec<Region> {
+        let mut accepted = vec![];
+        let mut pending = vec![(Target::Workflow(self.start), region)];
+        while let Some((target, region)) = pending.pop() {
+            let index = match target {
+                Target::Accept => {
+                    accepted.push(region);
+                    continue;
                 }
-                if matches[1].0 <= matches[1].1 {
-                    ranges[range_index] = matches[1];
+                Target::Reject => continue,
+                Target::Workflow(index) => index,
+            };
+            let workflow = &self.workflows[index];
+            let mut rest = Some(region);
+            for rule in &workflow.rules {
+                let Some(mut region) = rest else {
+                    break;
+                };
+                let category = rule.category.index();
+                let (matched, unmatched) = rule.split(region[category]);
+                if let Some(matched) = matched {
+                    let mut sub = region;
+                    sub[category] = matched;
+                    pending.push((rule.target, sub));
                 }
-
-                total
-            } else {
-                count(workflows, rule, ranges)
+                rest = unmatched.map(|unmatched| {
+                    region[category] = unmatched;
+                    region
+                });
             }
-        })
-        .sum()
+            if let Some(region) = rest {
+                pending.push((workflow.fallback, region));
+            }
+        }
+        accepted
+    }
+}
+
+pub fn parse_part(line: &str) -> Part {
+    let line = line.trim_start_matches('{').trim_end_matches('}');
+    let mut part = [0; 4];
+    for rating in line.split(',') {
+        let (category, value) = rating.split_once('=').unwrap();
+        let category = category.chars().next().and_then(Category::from).unwrap();
+        part[category.index()] = value.parse().unwrap();
+    }
+    part
 }
 
 #[aoc(day19, part1)]
-pub fn part1(input: &str) -> usize {
-    let (workflows, inputs) = input.split_once("\n\n").unwrap();
-    let workflows = workflows
+pub fn part1(input: &str) -> u64 {
+    let (workflows, parts) = input.split_once("\n\n").unwrap();
+    let workflows = workflows.parse::<Workflows>().unwrap();
+    parts
         .lines()
-        .map(|line| {
-            let (key, value) = line.split_once('{').unwrap();
-            let value = value.trim_end_matches('}').split(',').collect();
-            (key, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
-    inputs
-        .lines()
-        .filter_map(|line| {
-            let line = line.trim_start_matches('{').trim_end_matches('}');
-            let states = line
-                .split(',')
-                .map(|s| {
-                    let (_, value) = s.split_once('=').unwrap();
-                    value.parse::<usize>().unwrap()
-                })
-                .collect::<Vec<_>>();
-            if accept(&workflows, "in", &states) {
-                Some(states.iter().sum::<usize>())
-            } else {
-                None
-            }
-        })
+        .map(parse_part)
+        .filter(|part| workflows.accepts(part))
+        .map(|part| part.iter().sum::<u64>())
         .sum()
 }
 
 #[aoc(day19, part2)]
-pub fn part2(input: &str) -> usize {
-    let (workflows, _inputs) = input.split_once("\n\n").unwrap();
-    let workflows = workflows
-        .lines()
-        .map(|line| {
-            let (key, value) = line.split_once('{').unwrap();
-            let value = value.trim_end_matches('}').split(',').collect();
-            (key, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
-
-    let mut ranges = vec![(1, 4000), (1, 4000), (1, 4000), (1, 4000)];
-    count(&workflows, "in", &mut ranges)
+pub fn part2(input: &str) -> u64 {
+    let (workflows, _parts) = input.split_once("\n\n").unwrap();
+    let workflows = workflows.parse::<Workflows>().unwrap();
+    workflows.accepted([(1, 4000); 4]).iter().map(volume).sum()
 }
 
 #[cfg(test)]
@@ -180,4 +367,64 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 167409079868000);
     }
+
+    #[test]
+    fn test_accepted_regions() {
+        let (workflows, _) = SAMPLE.split_once("\n\n").unwrap();
+        let workflows = workflows.parse::<Workflows>().unwrap();
+        let region = [(1400, 1420), (830, 850), (1710, 1720), (530, 545)];
+        let accepted = workflows.accepted(region);
+
+        let mut brute_force = 0;
+        for x in 1400..=1420 {
+            for m in 830..=850 {
+                for a in 1710..=1720 {
+                    for s in 530..=545 {
+                        brute_force += workflows.accepts(&[x, m, a, s]) as u64;
+                    }
+                }
+            }
+        }
+        assert_eq!(accepted.iter().map(volume).sum::<u64>(), brute_force);
+        for region in &accepted {
+            assert!(workflows.accepts(&region.map(|r| r.0)));
+            assert!(workflows.accepts(&region.map(|r| r.1)));
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        assert_eq!(
+            "in{x<5:A,foo}".parse::<Workflows>(),
+            Err(WorkflowError::UnknownTarget {
+                workflow: String::from("in"),
+                target: String::from("foo")
+            })
+        );
+        assert_eq!(
+            "in{x<5:a,R}\na{m>3:b,A}\nb{a}".parse::<Workflows>(),
+            Err(WorkflowError::Cycle(vec![
+                String::from("a"),
+                String::from("b"),
+                String::from("a")
+            ]))
+        );
+        assert_eq!(
+            "in{x=5:A,R}".parse::<Workflows>(),
+            Err(WorkflowError::Syntax(String::from("in{x=5:A,R}")))
+        );
+        assert_eq!(
+            "start{A}".parse::<Workflows>(),
+            Err(WorkflowError::MissingStart)
+        );
+    }
+
+    #[test]
+    fn test_error_into_box() {
+        let parse = |s: &str| -> Result<Workflows, Box<dyn std::error::Error>> { Ok(s.parse()?) };
+        assert_eq!(
+            parse("start{A}").unwrap_err().to_string(),
+            "no `in` workflow"
+        );
+    }
 }
@@ -1,188 +1,320 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
 
+use aoc_common::math::crt;
 use aoc_runner_derive::aoc;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-enum Module<'a> {
+enum Module {
     FlipFlop(bool),
-    Conjunction(HashMap<&'a str, Pulse>),
+    Conjunction(Vec<(usize, Pulse)>),
     Broadcaster,
+    Output,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Pulse {
+pub enum Pulse {
     High,
     Low,
 }
 
-#[aoc(day20, part1)]
-pub fn part1(input: &str) -> usize {
-    let (module_connections, mut module_states) = input.lines().fold(
-        (HashMap::new(), HashMap::new()),
-        |(mut module_connections, mut module_states), line| {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signal {
+    pub from: Option<usize>,
+    pub to: usize,
+    pub pulse: Pulse,
+}
+
+#[derive(Debug, Clone)]
+pub struct PulseNetwork {
+    names: Vec<String>,
+    modules: Vec<Module>,
+    outputs: Vec<Vec<usize>>,
+    inputs: Vec<Vec<usize>>,
+    queue: VecDeque<Signal>,
+    presses: usize,
+}
+
+// A chain of flip-flops driven by the broadcaster that counts button presses.
+// The hub conjunction fires once the counter reaches `period`, and its low
+// pulses to the zero bits and the first bit wrap the counter back to zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counter {
+    pub bits: Vec<usize>,
+    pub hub: usize,
+    pub period: usize,
+}
+
+impl PulseNetwork {
+    pub fn from(input: &str) -> Self {
+        let mut network = PulseNetwork {
+            names: vec![],
+            modules: vec![],
+            outputs: vec![],
+            inputs: vec![],
+            queue: VecDeque::new(),
+            presses: 0,
+        };
+        for line in input.lines() {
             let (src, connections) = line.split_once(" -> ").unwrap();
-            let (name, module_type) = match src.as_bytes()[0] {
+            let (name, module) = match src.as_bytes()[0] {
                 b'%' => (&src[1..], Module::FlipFlop(false)),
-                b'&' => (&src[1..], Module::Conjunction(HashMap::new())),
-                b'b' => (src, Module::Broadcaster),
-                _ => unreachable!(),
+                b'&' => (&src[1..], Module::Conjunction(vec![])),
+                _ if src == "broadcaster" => (src, Module::Broadcaster),
+                _ => panic!("unknown module `{src}`"),
             };
-            module_connections.insert(name, connections.split(", ").collect::<Vec<_>>());
-            module_states.insert(name, module_type);
-            (module_connections, module_states)
-        },
-    );
-
-    for (&name, nexts) in &module_connections {
-        for &next in nexts {
-            if let Some(module) = module_states.get_mut(next) {
-                if let Module::Conjunction(ref mut input_map) = module {
-                    input_map.insert(name, Pulse::Low);
+            let from = network.intern(name);
+            network.modules[from] = module;
+            for next in connections.split(", ") {
+                let to = network.intern(next);
+                network.outputs[from].push(to);
+            }
+        }
+
+        for from in 0..network.modules.len() {
+            for i in 0..network.outputs[from].len() {
+                let to = network.outputs[from][i];
+                network.inputs[to].push(from);
+                if let Module::Conjunction(memory) = &mut network.modules[to] {
+                    memory.push((from, Pulse::Low));
                 }
             }
         }
+        network
     }
 
-    let mut queue = VecDeque::new();
-    let (mut high_count, mut low_count) = (0, 0);
-    for _ in 0..1000 {
-        queue.push_back(("button", Pulse::Low, "broadcaster"));
-        while let Some((from, pulse, to)) = queue.pop_front() {
-            match pulse {
-                Pulse::High => high_count += 1,
-                Pulse::Low => low_count += 1,
-            }
+    fn intern(&mut self, name: &str) -> usize {
+        if let Some(index) = self.index(name) {
+            return index;
+        }
+        self.names.push(name.to_string());
+        self.modules.push(Module::Output);
+        self.outputs.push(vec![]);
+        self.inputs.push(vec![]);
+        self.names.len() - 1
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    pub fn name(&self, index: usize) -> &str {
+        &self.names[index]
+    }
 
-            let next_pulse = match module_states.get_mut(to) {
-                Some(Module::FlipFlop(on)) => {
-                    if pulse == Pulse::High {
-                        continue;
-                    } else {
-                        *on = !*on;
-                        if *on {
-                            Pulse::High
-                        } else {
-                            Pulse::Low
-                        }
-                    }
+    pub fn presses(&self) -> usize {
+        self.presses
+    }
+
+    pub fn push_button(&mut self) {
+        let broadcaster = self.index("broadcaster").expect("no broadcaster");
+        self.presses += 1;
+        self.queue.push_back(Signal {
+            from: None,
+            to: broadcaster,
+            pulse: Pulse::Low,
+        });
+    }
+
+    // Delivers the next queued pulse and returns it, or None once the network
+    // has settled.
+    pub fn step(&mut self) -> Option<Signal> {
+        let signal = self.queue.pop_front()?;
+        let pulse = match &mut self.modules[signal.to] {
+            Module::FlipFlop(on) => {
+                if signal.pulse == Pulse::High {
+                    return Some(signal);
                 }
-                Some(Module::Conjunction(input_map)) => {
-                    *input_map.get_mut(from).unwrap() = pulse;
-                    if input_map.values().any(|pulse| *pulse == Pulse::Low) {
-                        Pulse::High
-                    } else {
-                        Pulse::Low
-                    }
+                *on = !*on;
+                if *on {
+                    Pulse::High
+                } else {
+                    Pulse::Low
                 }
-                Some(Module::Broadcaster) => Pulse::Low,
-                None => continue,
-            };
+            }
+            Module::Conjunction(memory) => {
+                let from = signal.from.unwrap();
+                memory.iter_mut().find(|(i, _)| *i == from).unwrap().1 = signal.pulse;
+                if memory.iter().any(|&(_, pulse)| pulse == Pulse::Low) {
+                    Pulse::High
+                } else {
+                    Pulse::Low
+                }
+            }
+            Module::Broadcaster => signal.pulse,
+            Module::Output => return Some(signal),
+        };
+        self.queue
+            .extend(self.outputs[signal.to].iter().map(|&to| Signal {
+                from: Some(signal.to),
+                to,
+                pulse,
+            }));
+        Some(signal)
+    }
 
-            queue.extend(
-                module_connections[to]
-                    .iter()
-                    .map(|&next| (to, next_pulse, next)),
-            );
+    // Pushes the button once and runs the network until it settles, showing
+    // every pulse (including the button's) to the observer.
+    pub fn press(&mut self, mut observer: impl FnMut(&Signal)) {
+        self.push_button();
+        while let Some(signal) = self.step() {
+            observer(&signal);
         }
     }
 
-    high_count * low_count
-}
-
-#[aoc(day20, part2)]
-pub fn part2(input: &str) -> usize {
-    let (module_connections, mut module_states) = input.lines().fold(
-        (HashMap::new(), HashMap::new()),
-        |(mut module_connections, mut module_states), line| {
-            let (src, connections) = line.split_once(" -> ").unwrap();
-            let (name, module_type) = match src.as_bytes()[0] {
-                b'%' => (&src[1..], Module::FlipFlop(false)),
-                b'&' => (&src[1..], Module::Conjunction(HashMap::new())),
-                b'b' => (src, Module::Broadcaster),
-                _ => unreachable!(),
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for (i, name) in self.names.iter().enumerate() {
+            let shape = match self.modules[i] {
+                Module::FlipFlop(_) => "box",
+                Module::Conjunction(_) => "diamond",
+                Module::Broadcaster => "doublecircle",
+                Module::Output => "plaintext",
             };
-            module_connections.insert(name, connections.split(", ").collect::<Vec<_>>());
-            module_states.insert(name, module_type);
-            (module_connections, module_states)
-        },
-    );
-
-    let mut rx_previous = "";
-    for (&name, nexts) in &module_connections {
-        for &next in nexts {
-            match module_states.get_mut(next) {
-                Some(Module::Conjunction(ref mut input_map)) => {
-                    input_map.insert(name, Pulse::Low);
-                }
-                Some(_) => {}
-                None => {
-                    rx_previous = name;
-                }
+            dot.push_str(&format!("  {name} [shape={shape}];\n"));
+        }
+        for (i, name) in self.names.iter().enumerate() {
+            for &next in &self.outputs[i] {
+                dot.push_str(&format!("  {name} -> {};\n", self.names[next]));
             }
         }
+        dot.push('}');
+        dot
     }
 
-    let mut rx_previous_counts = {
-        let Module::Conjunction(input_map) = &module_states[rx_previous] else {
-            panic!("Expected a conjunction module");
+    fn is_flip_flop(&self, index: usize) -> bool {
+        matches!(self.modules[index], Module::FlipFlop(_))
+    }
+
+    fn is_conjunction(&self, index: usize) -> bool {
+        matches!(self.modules[index], Module::Conjunction(_))
+    }
+
+    // Recognizes `target` being fed by one conjunction whose inputs are each an
+    // inverter on the hub of a binary counter.
+    pub fn counters(&self, target: &str) -> Option<Vec<Counter>> {
+        let target = self.index(target)?;
+        let &[feeder] = self.inputs[target].as_slice() else {
+            return None;
         };
-        input_map
-            .keys()
-            .map(|&name| (name, None))
-            .collect::<HashMap<_, _>>()
-    };
-
-    let mut queue = VecDeque::new();
-    for count in 1.. {
-        queue.push_back(("button", Pulse::Low, "broadcaster"));
-        while let Some((from, pulse, to)) = queue.pop_front() {
-            if pulse == Pulse::High && to == rx_previous {
-                let rx_prevous_count = rx_previous_counts.get_mut(from).unwrap();
-                if rx_prevous_count.is_none() {
-                    *rx_prevous_count = Some(count);
-                    if rx_previous_counts.values().all(|c| c.is_some()) {
-                        return rx_previous_counts.values().fold(1, |mut answer, c| {
-                            answer *= c.unwrap();
-                            answer
-                        });
-                    }
+        if !self.is_conjunction(feeder) {
+            return None;
+        }
+        self.inputs[feeder]
+            .iter()
+            .map(|&inverter| match self.inputs[inverter].as_slice() {
+                &[hub] if self.is_conjunction(inverter) && self.is_conjunction(hub) => {
+                    self.counter(hub, inverter)
                 }
-            }
+                _ => None,
+            })
+            .collect()
+    }
 
-            let next_pulse = match module_states.get_mut(to) {
-                Some(Module::FlipFlop(on)) => {
-                    if pulse == Pulse::High {
-                        continue;
-                    } else {
-                        *on = !*on;
-                        if *on {
-                            Pulse::High
-                        } else {
-                            Pulse::Low
-                        }
-                    }
-                }
-                Some(Module::Conjunction(input_map)) => {
-                    *input_map.get_mut(from).unwrap() = pulse;
-                    if input_map.values().any(|pulse| *pulse == Pulse::Low) {
-                        Pulse::High
-                    } else {
-                        Pulse::Low
-                    }
-                }
-                Some(Module::Broadcaster) => Pulse::Low,
-                None => continue,
-            };
+    fn counter(&self, hub: usize, inverter: usize) -> Option<Counter> {
+        let broadcaster = self.index("broadcaster")?;
+        let first = self.inputs[hub].first()?;
+        let bits = self.outputs[broadcaster]
+            .iter()
+            .map(|&start| self.chain(start))
+            .find(|bits| bits.contains(first))?;
 
-            queue.extend(
-                module_connections[to]
+        let mut period = 0;
+        for (i, &bit) in bits.iter().enumerate() {
+            if self.outputs[bit].contains(&hub) {
+                period |= 1 << i;
+            }
+            let others_ok = self.outputs[bit]
+                .iter()
+                .all(|&next| next == hub || bits.get(i + 1) == Some(&next));
+            if !others_ok
+                || !self.inputs[bit]
                     .iter()
-                    .map(|&next| (to, next_pulse, next)),
-            );
+                    .all(|&from| from == hub || bits.contains(&from) || from == broadcaster)
+            {
+                return None;
+            }
+        }
+
+        let mut resets = (0..bits.len())
+            .filter(|&i| i == 0 || period & (1 << i) == 0)
+            .map(|i| bits[i])
+            .chain([inverter])
+            .collect::<Vec<_>>();
+        let mut hub_outputs = self.outputs[hub].clone();
+        resets.sort_unstable();
+        hub_outputs.sort_unstable();
+
+        let fed = self.inputs[hub].iter().all(|from| bits.contains(from));
+        let top = period >> (bits.len() - 1) == 1;
+        (fed && top && period & 1 == 1 && resets == hub_outputs).then_some(Counter {
+            bits,
+            hub,
+            period,
+        })
+    }
+
+    fn chain(&self, start: usize) -> Vec<usize> {
+        let mut bits = vec![];
+        let mut current = Some(start);
+        while let Some(bit) = current.filter(|&bit| self.is_flip_flop(bit)) {
+            bits.push(bit);
+            let mut next = self.outputs[bit].iter().filter(|&&n| self.is_flip_flop(n));
+            current = next.next().copied();
+            if next.next().is_some() {
+                return vec![];
+            }
         }
+        bits
+    }
+}
+
+pub fn count_pulses(input: &str, presses: usize) -> (usize, usize) {
+    let mut network = PulseNetwork::from(input);
+    let (mut high, mut low) = (0, 0);
+    for _ in 0..presses {
+        network.press(|signal| match signal.pulse {
+            Pulse::High => high += 1,
+            Pulse::Low => low += 1,
+        });
+    }
+    (high, low)
+}
+
+// Each counter first fires on press `period` and then every `period` presses,
+// and `target` gets its low pulse once they all fire on the same press.
+pub fn presses_until_low(input: &str, target: &str) -> usize {
+    let network = PulseNetwork::from(input);
+    if let Some(counters) = network.counters(target) {
+        let periods = counters.iter().map(|c| c.period as u64);
+        let (residue, modulus) = crt(periods.clone().map(|p| (p, p))).expect("no common press");
+        let first = periods.max().unwrap_or(0);
+        return (residue + first.saturating_sub(residue).div_ceil(modulus) * modulus) as usize;
     }
 
-    unreachable!()
+    brute_force(input, target)
+}
+
+pub fn brute_force(input: &str, target: &str) -> usize {
+    let mut network = PulseNetwork::from(input);
+    let target = network.index(target).expect("unknown target");
+    loop {
+        let mut reached = false;
+        network.press(|signal| reached |= signal.to == target && signal.pulse == Pulse::Low);
+        if reached {
+            return network.presses();
+        }
+    }
+}
+
+#[aoc(day20, part1)]
+pub fn part1(input: &str) -> usize {
+    let (high, low) = count_pulses(input, 1000);
+    high * low
+}
+
+#[aoc(day20, part2)]
+pub fn part2(input: &str) -> usize {
+    presses_until_low(input, "rx")
 }
 
 #[cfg(test)]
@@ -190,6 +322,7 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::gen::counter_network;
 
     const SAMPLE_1: &str = indoc! {"
         broadcaster -> a, b, c
@@ -212,4 +345,83 @@ mod tests {
         assert_eq!(part1(SAMPLE_1), 32000000);
         assert_eq!(part1(SAMPLE_2), 11687500);
     }
+
+    #[test]
+    fn test_counters() {
+        let input = counter_network(&[11, 13, 7]);
+        let network = PulseNetwork::from(&input);
+        let periods = network
+            .counters("rx")
+            .unwrap()
+            .iter()
+            .map(|c| c.period)
+            .collect::<Vec<_>>();
+        assert_eq!(periods, [11, 13, 7]);
+        assert_eq!(part2(&input), 1001);
+        assert_eq!(brute_force(&input, "rx"), 1001);
+
+        let input = counter_network(&[9, 15]);
+        assert_eq!(part2(&input), 45);
+        assert_eq!(brute_force(&input, "rx"), 45);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let input = SAMPLE_2.replace("output", "rx");
+        assert_eq!(PulseNetwork::from(&input).counters("rx"), None);
+        assert_eq!(part2(&input), brute_force(&input, "rx"));
+    }
+
+    #[test]
+    fn test_unfed_hub() {
+        let input = indoc! {"
+            broadcaster -> a
+            %a -> b
+            &h -> i
+            &i -> f
+            &f -> rx
+        "};
+        assert_eq!(PulseNetwork::from(input).counters("rx"), None);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut network = PulseNetwork::from(SAMPLE_1);
+        network.push_button();
+        let mut trace = vec![];
+        while let Some(signal) = network.step() {
+            let from = signal.from.map_or("button", |from| network.name(from));
+            trace.push(format!(
+                "{from} -{:?}-> {}",
+                signal.pulse,
+                network.name(signal.to)
+            ));
+        }
+        assert_eq!(trace.len(), 12);
+        assert_eq!(trace[0], "button -Low-> broadcaster");
+        assert_eq!(trace[11], "inv -High-> a");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let dot = PulseNetwork::from(SAMPLE_2).to_dot();
+        assert_eq!(
+            dot,
+            indoc! {"
+                digraph {
+                  broadcaster [shape=doublecircle];
+                  a [shape=box];
+                  inv [shape=diamond];
+                  con [shape=diamond];
+                  b [shape=box];
+                  output [shape=plaintext];
+                  broadcaster -> a;
+                  a -> inv;
+                  a -> con;
+                  inv -> b;
+                  con -> output;
+                  b -> con;
+                }"}
+        );
+    }
 }
@@ -1,7 +1,40 @@
+use std::collections::HashSet;
+
+use aoc_common::params::Overrides;
 use aoc_runner_derive::aoc;
 
 const DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub steps: usize,
+    pub infinite_steps: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            steps: 64,
+            infinite_steps: 26501365,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params {
+        steps: 6,
+        infinite_steps: 500,
+    };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2023, "day21", "steps", &mut params.steps);
+        overrides.apply(2023, "day21", "infinite_steps", &mut params.infinite_steps);
+        params
+    }
+}
+
 fn cal_positions(grid: &Vec<Vec<char>>, start: (usize, usize), steps: usize) -> usize {
     let (height, width) = (grid.len(), grid[0].len());
 
@@ -37,8 +70,7 @@ fn cal_positions(grid: &Vec<Vec<char>>, start: (usize, usize), steps: usize) ->
         .count()
 }
 
-#[aoc(day21, part1)]
-pub fn part1(input: &str) -> usize {
+pub fn part1_with(input: &str, params: &Params) -> usize {
     let (grid, start) = input
         .lines()
         .fold((vec![], (0, 0)), |(mut grid, mut start), line| {
@@ -50,11 +82,60 @@ pub fn part1(input: &str) -> usize {
             (grid, start)
         });
 
-    cal_positions(&grid, start, 64)
+    cal_positions(&grid, start, params.steps)
 }
 
-#[aoc(day21, part2)]
-pub fn part2(input: &str) -> usize {
+#[aoc(day21, part1)]
+pub fn part1(input: &str) -> usize {
+    part1_with(input, &Params::from_env())
+}
+
+// The tile-counting shortcut needs a square grid with the start in the middle,
+// an open start row, start column and border, and a walk that ends on a tile edge.
+fn has_clear_lanes(grid: &[Vec<char>], start: (usize, usize), steps: usize) -> bool {
+    let (height, width) = (grid.len(), grid[0].len());
+    let open_row = |r: usize| grid[r].iter().all(|&c| c == '.');
+    let open_col = |c: usize| grid.iter().all(|row| row[c] == '.');
+    height == width
+        && start == (height / 2, width / 2)
+        && steps % height == height / 2
+        && steps >= height
+        && open_row(start.0)
+        && open_col(start.1)
+        && open_row(0)
+        && open_row(height - 1)
+        && open_col(0)
+        && open_col(width - 1)
+}
+
+// Breadth-first search over the repeating garden, counting the plots whose
+// distance has the same parity as the step count.
+fn count_infinite(grid: &[Vec<char>], start: (usize, usize), steps: usize) -> usize {
+    let (height, width) = (grid.len() as isize, grid[0].len() as isize);
+    let start = (start.0 as isize, start.1 as isize);
+    let mut seen = HashSet::from([start]);
+    let mut frontier = vec![start];
+    let mut count = (steps % 2 == 0) as usize;
+    for step in 1..=steps {
+        let mut next = vec![];
+        for (r, c) in frontier {
+            for (dr, dc) in &DIRECTIONS {
+                let (nr, nc) = (r + dr, c + dc);
+                let tile = grid[nr.rem_euclid(height) as usize][nc.rem_euclid(width) as usize];
+                if tile == '.' && seen.insert((nr, nc)) {
+                    next.push((nr, nc));
+                }
+            }
+        }
+        if step % 2 == steps % 2 {
+            count += next.len();
+        }
+        frontier = next;
+    }
+    count
+}
+
+pub fn part2_with(input: &str, params: &Params) -> usize {
     let (grid, start) = input
         .lines()
         .fold((vec![], (0, 0)), |(mut grid, mut start), line| {
@@ -66,22 +147,20 @@ pub fn part2(input: &str) -> usize {
             (grid, start)
         });
 
-    let steps = 26501365;
-    let (height, width) = (grid.len(), grid[0].len());
-    assert_eq!(height, width);
-    assert_eq!(start.0, start.1);
-    assert_eq!(start.0, height / 2);
-    assert_eq!(steps % height, height / 2);
+    let steps = params.infinite_steps;
+    if !has_clear_lanes(&grid, start, steps) {
+        return count_infinite(&grid, start, steps);
+    }
 
-    let steps = 26501365;
-    let size = height;
+    let size = grid.len();
     let grid_width = steps / size - 1;
 
+    // Tiles alternate parity outwards from the start tile, which shares the parity of `steps`
     let odd = (grid_width / 2 * 2 + 1).pow(2);
     let even = ((grid_width + 1) / 2 * 2).pow(2);
 
-    let odd_points = cal_positions(&grid, start, size * 2 + 1);
-    let even_points = cal_positions(&grid, start, size * 2);
+    let odd_points = cal_positions(&grid, start, size * 2 + steps % 2);
+    let even_points = cal_positions(&grid, start, size * 2 + 1 - steps % 2);
 
     let (sr, sc) = start;
 
@@ -113,11 +192,18 @@ pub fn part2(input: &str) -> usize {
         + grid_width * (large_tr + large_tl + large_br + large_bl)
 }
 
+#[aoc(day21, part2)]
+pub fn part2(input: &str) -> usize {
+    part2_with(input, &Params::from_env())
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
+    use aoc_common::rng::Rng;
 
     const SAMPLE: &str = indoc! {"
         ...........
@@ -135,6 +221,45 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(SAMPLE), 42);
+        assert_eq!(part1_with(SAMPLE, &Params::default()), 42);
+        assert_eq!(part1_with(SAMPLE, &Params::SAMPLE), 16);
+    }
+
+    #[test]
+    fn test_part2() {
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), 167004);
+        let params = Params {
+            infinite_steps: 100,
+            ..Params::SAMPLE
+        };
+        assert_eq!(part2_with(SAMPLE, &params), 6536);
+    }
+
+    #[test]
+    fn test_lanes_against_search() {
+        let mut rng = Rng::new(21);
+        for size in [5, 11, 21] {
+            let input = gen::day21(&mut rng, size);
+            let side = input.lines().count();
+            let params = Params {
+                steps: size,
+                infinite_steps: side * 4 + side / 2,
+            };
+            let mut grid = input
+                .lines()
+                .map(|line| line.chars().collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            grid[side / 2][side / 2] = '.';
+            assert!(has_clear_lanes(
+                &grid,
+                (side / 2, side / 2),
+                params.infinite_steps
+            ));
+            assert_eq!(
+                part2_with(&input, &params),
+                count_infinite(&grid, (side / 2, side / 2), params.infinite_steps),
+                "{input}"
+            );
+        }
     }
 }
@@ -164,4 +164,47 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 154);
     }
+
+    fn brute_force_walk(
+        grid: &[&[u8]],
+        seen: &mut [Vec<bool>],
+        (r, c): (usize, usize),
+    ) -> Option<usize> {
+        if (r, c) == (grid.len() - 1, grid[0].len() - 2) {
+            return Some(0);
+        }
+        let mut longest = None;
+        for (dr, dc) in DIRECTIONS {
+            let (rr, cc) = ((r as isize + dr) as usize, (c as isize + dc) as usize);
+            if grid.get(rr).and_then(|row| row.get(cc)) == Some(&b'.') && !seen[rr][cc] {
+                seen[rr][cc] = true;
+                if let Some(len) = brute_force_walk(grid, seen, (rr, cc)) {
+                    longest = longest.max(Some(len + 1));
+                }
+                seen[rr][cc] = false;
+            }
+        }
+        longest
+    }
+
+    fn brute_force(input: &str) -> usize {
+        let grid = input
+            .lines()
+            .map(|line| line.as_bytes())
+            .collect::<Vec<_>>();
+        let mut seen = vec![vec![false; grid[0].len()]; grid.len()];
+        seen[0][1] = true;
+        brute_force_walk(&grid, &mut seen, (0, 1)).unwrap()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = aoc_common::rng::Rng::new(23);
+        for size in [5, 7, 9, 11] {
+            let input = crate::gen::day23(&mut rng, size);
+            let expected = brute_force(&input);
+            assert_eq!(part1(&input), expected, "{input}");
+            assert_eq!(part2(&input), expected, "{input}");
+        }
+    }
 }
@@ -1,6 +1,46 @@
+use aoc_common::params::Overrides;
 use aoc_runner_derive::aoc;
+#[cfg(all(test, feature = "z3"))]
 use z3::ast::{Ast, Int, Real};
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub min_x: f64,
+    pub max_x: f64,
+    pub min_y: f64,
+    pub max_y: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            min_x: 200000000000000.0,
+            max_x: 400000000000000.0,
+            min_y: 200000000000000.0,
+            max_y: 400000000000000.0,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params {
+        min_x: 7.0,
+        max_x: 27.0,
+        min_y: 7.0,
+        max_y: 27.0,
+    };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2023, "day24", "min_x", &mut params.min_x);
+        overrides.apply(2023, "day24", "max_x", &mut params.max_x);
+        overrides.apply(2023, "day24", "min_y", &mut params.min_y);
+        overrides.apply(2023, "day24", "max_y", &mut params.max_y);
+        params
+    }
+}
+
 fn parse(input: &str) -> Vec<Vec<f64>> {
     input
         .lines()
@@ -20,7 +60,7 @@ fn parse(input: &str) -> Vec<Vec<f64>> {
         .collect()
 }
 
-fn solve_part1(input: &str, (min_x, max_x, min_y, max_y): (f64, f64, f64, f64)) -> usize {
+pub fn part1_with(input: &str, params: &Params) -> usize {
     let hailstones = parse(input);
 
     hailstones
@@ -48,26 +88,172 @@ fn solve_part1(input: &str, (min_x, max_x, min_y, max_y): (f64, f64, f64, f64))
                 // Not in the same direction
                 return false;
             }
-            x >= min_x && x <= max_x && y >= min_y && y <= max_y
+            x >= params.min_x && x <= params.max_x && y >= params.min_y && y <= params.max_y
         })
         .count()
 }
 
 #[aoc(day24, part1)]
 pub fn part1(input: &str) -> usize {
-    solve_part1(
-        input,
-        (
-            200000000000000.0,
-            400000000000000.0,
-            200000000000000.0,
-            400000000000000.0,
-        ),
-    )
+    part1_with(input, &Params::from_env())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    fn new(num: i128, den: i128) -> Option<Rational> {
+        if den == 0 {
+            return None;
+        }
+        let g = gcd(num, den) * den.signum();
+        Some(Rational {
+            num: num / g,
+            den: den / g,
+        })
+    }
+
+    fn from_int(value: i128) -> Rational {
+        Rational { num: value, den: 1 }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    fn sub(self, other: Rational) -> Option<Rational> {
+        let g = gcd(self.den, other.den);
+        let num = self
+            .num
+            .checked_mul(other.den / g)?
+            .checked_sub(other.num.checked_mul(self.den / g)?)?;
+        Rational::new(num, self.den.checked_mul(other.den / g)?)
+    }
+
+    fn mul(self, other: Rational) -> Option<Rational> {
+        let g1 = gcd(self.num, other.den).max(1);
+        let g2 = gcd(other.num, self.den).max(1);
+        Rational::new(
+            (self.num / g1).checked_mul(other.num / g2)?,
+            (self.den / g2).checked_mul(other.den / g1)?,
+        )
+    }
+
+    fn div(self, other: Rational) -> Option<Rational> {
+        self.mul(Rational::new(other.den, other.num)?)
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn cross(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn gaussian_elimination<const N: usize, const M: usize>(
+    mut matrix: [[Rational; M]; N],
+) -> Option<[Rational; N]> {
+    for col in 0..N {
+        let pivot = (col..N).find(|&r| !matrix[r][col].is_zero())?;
+        matrix.swap(col, pivot);
+        let pivot_row = matrix[col];
+        for (r, row) in matrix.iter_mut().enumerate() {
+            if r == col || row[col].is_zero() {
+                continue;
+            }
+            let factor = row[col].div(pivot_row[col])?;
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row).skip(col) {
+                *value = value.sub(factor.mul(pivot_value)?)?;
+            }
+        }
+    }
+    let mut solution = [Rational::from_int(0); N];
+    for (i, row) in matrix.iter().enumerate() {
+        solution[i] = row[M - 1].div(row[i])?;
+    }
+    Some(solution)
+}
+
+// For a hailstone (p_i, v_i), the rock (p, v) collides with it iff
+// (p - p_i) x (v - v_i) = 0. Subtracting the equations of two hailstones
+// cancels the quadratic term p x v, leaving
+// p x (v_i - v_j) + (p_i - p_j) x v = p_i x v_i - p_j x v_j.
+fn linear_rows(a: &[i128; 6], b: &[i128; 6]) -> [[i128; 7]; 3] {
+    let (pa, va) = ([a[0], a[1], a[2]], [a[3], a[4], a[5]]);
+    let (pb, vb) = ([b[0], b[1], b[2]], [b[3], b[4], b[5]]);
+    let dv = [va[0] - vb[0], va[1] - vb[1], va[2] - vb[2]];
+    let dp = [pa[0] - pb[0], pa[1] - pb[1], pa[2] - pb[2]];
+    let (ca, cb) = (cross(pa, va), cross(pb, vb));
+    let c = [ca[0] - cb[0], ca[1] - cb[1], ca[2] - cb[2]];
+    [
+        [0, dv[2], -dv[1], 0, -dp[2], dp[1], c[0]],
+        [-dv[2], 0, dv[0], dp[2], 0, -dp[0], c[1]],
+        [dv[1], -dv[0], 0, -dp[1], dp[0], 0, c[2]],
+    ]
+}
+
+fn solve_rock(hailstones: &[[i128; 6]; 3]) -> Option<[i128; 6]> {
+    // Solving in the frame of the first hailstone keeps the intermediate
+    // fractions small enough for i128.
+    let origin = hailstones[0];
+    let relative = hailstones.map(|h| std::array::from_fn::<i128, 6, _>(|i| h[i] - origin[i]));
+
+    let mut matrix = [[Rational::from_int(0); 7]; 6];
+    let rows = [
+        linear_rows(&relative[0], &relative[1]),
+        linear_rows(&relative[0], &relative[2]),
+    ];
+    for (i, row) in rows.iter().flatten().enumerate() {
+        matrix[i] = row.map(Rational::from_int);
+    }
+
+    let solution = gaussian_elimination(matrix)?;
+    let mut rock = [0; 6];
+    for i in 0..6 {
+        if solution[i].den != 1 {
+            return None;
+        }
+        rock[i] = solution[i].num + origin[i];
+    }
+    Some(rock)
+}
+
+fn collides(rock: &[i128; 6], hailstone: &[i128; 6]) -> bool {
+    let dp = [0, 1, 2].map(|i| rock[i] - hailstone[i]);
+    let dv = [3, 4, 5].map(|i| rock[i] - hailstone[i]);
+    cross(dp, dv) == [0, 0, 0]
 }
 
 #[aoc(day24, part2)]
-pub fn part2(input: &str) -> usize {
+pub fn part2(input: &str) -> i128 {
+    let hailstones = parse(input)
+        .iter()
+        .map(|h| std::array::from_fn::<i128, 6, _>(|i| h[i] as i128))
+        .collect::<Vec<_>>();
+
+    hailstones
+        .windows(3)
+        .filter_map(|w| solve_rock(&[w[0], w[1], w[2]]))
+        .find(|rock| hailstones.iter().all(|h| collides(rock, h)))
+        .map(|rock| rock[0] + rock[1] + rock[2])
+        .unwrap()
+}
+
+#[cfg(all(test, feature = "z3"))]
+fn part2_z3(input: &str) -> usize {
     let hailstones = parse(input);
 
     let ctx = z3::Context::new(&z3::Config::new());
@@ -117,11 +303,22 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(solve_part1(SAMPLE, (7.0, 27.0, 7.0, 27.0)), 2);
+        assert_eq!(part1_with(SAMPLE, &Params::SAMPLE), 2);
+        let narrow = Params {
+            max_y: 16.0,
+            ..Params::SAMPLE
+        };
+        assert_eq!(part1_with(SAMPLE, &narrow), 1);
     }
 
     #[test]
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 47);
     }
+
+    #[cfg(feature = "z3")]
+    #[test]
+    fn test_part2_z3() {
+        assert_eq!(part2_z3(SAMPLE), 47);
+    }
 }
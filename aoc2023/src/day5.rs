@@ -1,133 +1,203 @@
 use aoc_runner_derive::aoc;
 
-#[aoc(day5, part1)]
-pub fn part1(input: &str) -> usize {
-    let inputs = input.split("\n\n").collect::<Vec<_>>();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Piece {
+    start: u64,
+    offset: i64,
+}
 
-    let seeds = inputs[0]
-        .trim_start_matches("seeds: ")
-        .split(" ")
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
+// A function over all of u64 made of sorted pieces. Each piece shifts every
+// number from its start up to the next piece's start by the same offset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PiecewiseMap {
+    pieces: Vec<Piece>,
+}
 
-    let mut layers = inputs
-        .iter()
-        .skip(1)
-        .map(|line| {
-            line.lines()
-                .skip(1)
-                .map(|line| {
-                    line.split(" ")
-                        .map(|s| s.parse::<usize>().unwrap())
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-    layers.iter_mut().for_each(|layer| {
-        layer.sort_by(|a, b| a[1].cmp(&b[1]));
-    });
-
-    seeds
-        .iter()
-        .map(|seed| {
-            let mut number = seed.clone();
-            for layer in &layers {
-                number = layer
-                    .iter()
-                    .find_map(|mapping| {
-                        let (begin, end, target) =
-                            (mapping[1], mapping[1] + mapping[2], mapping[0]);
-                        if number >= begin && number < end {
-                            Some(target + (number - begin))
-                        } else {
-                            None
-                        }
-                    })
-                    .unwrap_or(number);
-            }
-            number
-        })
-        .min()
-        .unwrap()
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub from: String,
+    pub to: String,
+    pub map: PiecewiseMap,
 }
 
-#[aoc(day5, part2)]
-pub fn part2(input: &str) -> usize {
-    let inputs = input.split("\n\n").collect::<Vec<_>>();
+#[derive(Debug, Clone)]
+pub struct Almanac {
+    pub seeds: Vec<u64>,
+    pub layers: Vec<Layer>,
+}
 
-    let seeds = inputs[0]
-        .trim_start_matches("seeds: ")
-        .split(" ")
-        .map(|s| s.parse::<usize>().unwrap())
-        .collect::<Vec<_>>();
+impl PiecewiseMap {
+    pub fn identity() -> Self {
+        PiecewiseMap {
+            pieces: vec![Piece {
+                start: 0,
+                offset: 0,
+            }],
+        }
+    }
 
-    let mut layers = inputs
-        .iter()
-        .skip(1)
-        .map(|line| {
-            line.lines()
-                .skip(1)
-                .map(|line| {
-                    line.split(" ")
-                        .map(|s| s.parse::<usize>().unwrap())
-                        .collect::<Vec<_>>()
-                })
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-    layers.iter_mut().for_each(|layer| {
-        layer.sort_by(|a, b| a[1].cmp(&b[1]));
-    });
-
-    let get_values = |layer: &Vec<Vec<usize>>, (start, end): (usize, usize)| {
-        let mut values = vec![];
-        for (index, mapping) in layer.iter().enumerate() {
-            let (mapping_start, mapping_end, target) =
-                (mapping[1], mapping[1] + mapping[2], mapping[0]);
-            if index == 0 {
-                if end < mapping_start {
-                    values.push((start, end));
-                } else if start < mapping_start {
-                    values.push((start, mapping_start));
-                }
+    // Takes `destination source length` lines; anything not covered maps to itself.
+    pub fn from(lines: &str) -> Self {
+        let mut ranges = lines
+            .lines()
+            .map(|line| {
+                let numbers = line
+                    .split_whitespace()
+                    .map(|n| n.parse::<u64>().unwrap())
+                    .collect::<Vec<_>>();
+                (numbers[1], numbers[0], numbers[2])
+            })
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+
+        let mut pieces = vec![];
+        let mut end = 0;
+        for (source, destination, length) in ranges {
+            assert!(source >= end, "overlapping ranges at {source}");
+            if source > end || pieces.is_empty() {
+                pieces.push(Piece {
+                    start: end,
+                    offset: 0,
+                });
             }
-            if index == layer.len() - 1 {
-                if start > mapping_end {
-                    values.push((start, end));
-                } else if end > mapping_end {
-                    values.push((mapping_end, end));
-                }
+            pieces.push(Piece {
+                start: source,
+                offset: destination as i64 - source as i64,
+            });
+            end = source + length;
+        }
+        pieces.push(Piece {
+            start: end,
+            offset: 0,
+        });
+        PiecewiseMap::normalized(pieces)
+    }
+
+    // Drops empty pieces and merges neighbours with the same offset.
+    fn normalized(pieces: Vec<Piece>) -> Self {
+        let mut merged: Vec<Piece> = vec![];
+        for piece in pieces {
+            match merged.last_mut() {
+                Some(last) if last.start == piece.start => *last = piece,
+                Some(last) if last.offset == piece.offset => {}
+                _ => merged.push(piece),
             }
-            if start < mapping_end && end > mapping_start {
-                let (start, end) = (mapping_start.max(start), mapping_end.min(end));
-                values.push((
-                    target + (start - mapping_start),
-                    target + (end - mapping_start),
-                ));
+        }
+        // Replacing an empty piece can leave equal offsets side by side.
+        merged.dedup_by(|b, a| a.offset == b.offset);
+        PiecewiseMap { pieces: merged }
+    }
+
+    // Exclusive end of the i-th piece.
+    fn end(&self, i: usize) -> i128 {
+        self.pieces
+            .get(i + 1)
+            .map_or(u64::MAX as i128 + 1, |next| next.start as i128)
+    }
+
+    fn piece_index(&self, x: u64) -> usize {
+        self.pieces.partition_point(|piece| piece.start <= x) - 1
+    }
+
+    pub fn apply(&self, x: u64) -> u64 {
+        (x as i64 + self.pieces[self.piece_index(x)].offset) as u64
+    }
+
+    // Applies `self` and then `then`.
+    pub fn compose(&self, then: &PiecewiseMap) -> PiecewiseMap {
+        let mut pieces = vec![];
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let offset = piece.offset as i128;
+            let (low, high) = (piece.start as i128 + offset, self.end(i) + offset);
+            let mut j = then.piece_index(low as u64);
+            while j < then.pieces.len() && (then.pieces[j].start as i128) < high {
+                let start = (then.pieces[j].start as i128).max(low);
+                pieces.push(Piece {
+                    start: (start - offset) as u64,
+                    offset: piece.offset + then.pieces[j].offset,
+                });
+                j += 1;
             }
         }
-        values
-    };
-
-    seeds
-        .windows(2)
-        .step_by(2)
-        .filter_map(|pair| {
-            let (start, end) = (pair[0], pair[0] + pair[1]);
-            let mut values = vec![(start, end)];
-            for layer in &layers {
-                let mut new_values = vec![];
-                for (start, end) in values {
-                    new_values.extend(get_values(layer, (start, end)));
+        PiecewiseMap::normalized(pieces)
+    }
+
+    // Every x with `self.apply(x) == y`, in increasing order.
+    pub fn inverse(&self, y: u64) -> Vec<u64> {
+        (0..self.pieces.len())
+            .filter_map(|i| {
+                let x = y as i128 - self.pieces[i].offset as i128;
+                (x >= self.pieces[i].start as i128 && x < self.end(i)).then_some(x as u64)
+            })
+            .collect()
+    }
+
+    // Smallest output over `start..start + length`, or None for an empty range.
+    pub fn min_over(&self, start: u64, length: u64) -> Option<u64> {
+        if length == 0 {
+            return None;
+        }
+        let end = start as i128 + length as i128;
+        (self.piece_index(start)..self.pieces.len())
+            .take_while(|&i| (self.pieces[i].start as i128) < end)
+            .map(|i| self.apply(self.pieces[i].start.max(start)))
+            .min()
+    }
+}
+
+impl Almanac {
+    pub fn from(input: &str) -> Self {
+        let mut sections = input.split("\n\n");
+        let seeds = sections
+            .next()
+            .unwrap()
+            .trim_start_matches("seeds: ")
+            .split_whitespace()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let layers = sections
+            .map(|section| {
+                let (header, lines) = section.split_once('\n').unwrap();
+                let (from, to) = header.trim_end_matches(" map:").split_once("-to-").unwrap();
+                Layer {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                    map: PiecewiseMap::from(lines),
                 }
-                values = new_values;
-            }
-            values
-                .into_iter()
-                .min_by(|a, b| a.0.cmp(&b.0))
-                .map(|(start, _)| start)
-        })
+            })
+            .collect();
+        Almanac { seeds, layers }
+    }
+
+    pub fn seed_to_location(&self) -> PiecewiseMap {
+        self.layers
+            .iter()
+            .fold(PiecewiseMap::identity(), |map, layer| {
+                map.compose(&layer.map)
+            })
+    }
+}
+
+#[aoc(day5, part1)]
+pub fn part1(input: &str) -> u64 {
+    let almanac = Almanac::from(input);
+    let map = almanac.seed_to_location();
+    almanac
+        .seeds
+        .iter()
+        .map(|&seed| map.apply(seed))
+        .min()
+        .unwrap()
+}
+
+#[aoc(day5, part2)]
+pub fn part2(input: &str) -> u64 {
+    let almanac = Almanac::from(input);
+    let map = almanac.seed_to_location();
+    almanac
+        .seeds
+        .chunks(2)
+        .filter_map(|range| map.min_over(range[0], range[1]))
         .min()
         .unwrap()
 }
@@ -137,6 +207,8 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
+    use aoc_common::rng::Rng;
 
     const SAMPLE: &str = indoc! {"
         seeds: 79 14 55 13
@@ -183,4 +255,85 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 46);
     }
+
+    #[test]
+    fn test_compose() {
+        let almanac = Almanac::from(SAMPLE);
+        let map = almanac.seed_to_location();
+        for seed in 0..120 {
+            let location = almanac
+                .layers
+                .iter()
+                .fold(seed, |number, layer| layer.map.apply(number));
+            assert_eq!(map.apply(seed), location);
+            assert!(map.inverse(location).contains(&seed));
+        }
+        assert_eq!(map.apply(79), 82);
+        assert_eq!(map.inverse(46), [82]);
+        assert_eq!(almanac.layers[0].from, "seed");
+        assert_eq!(almanac.layers[6].to, "location");
+    }
+
+    #[test]
+    fn test_min_over_edges() {
+        let map = PiecewiseMap::from("0 10 5");
+        assert_eq!(map.min_over(12, 0), None);
+        assert_eq!(map.min_over(10, 0), None);
+        assert_eq!(map.min_over(8, 4), Some(0));
+        assert_eq!(map.min_over(u64::MAX - 1, 5), Some(u64::MAX - 1));
+        assert_eq!(map.min_over(u64::MAX, u64::MAX), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_inverse_not_injective() {
+        let map = PiecewiseMap::from("0 10 5");
+        assert_eq!(map.inverse(2), [2, 12]);
+        assert_eq!(map.inverse(12), [] as [u64; 0]);
+        assert_eq!(map.inverse(20), [20]);
+    }
+
+    // Walks every seed through the maps line by line
+    fn brute_force_part2(input: &str) -> u64 {
+        let mut sections = input.split("\n\n");
+        let seeds = sections.next().unwrap()["seeds: ".len()..]
+            .split_whitespace()
+            .map(|s| s.parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        let maps = sections
+            .map(|section| {
+                section
+                    .lines()
+                    .skip(1)
+                    .map(|line| {
+                        let nums = line
+                            .split_whitespace()
+                            .map(|s| s.parse::<u64>().unwrap())
+                            .collect::<Vec<_>>();
+                        (nums[0], nums[1], nums[2])
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        seeds
+            .chunks(2)
+            .flat_map(|range| range[0]..range[0] + range[1])
+            .map(|seed| {
+                maps.iter().fold(seed, |x, map| {
+                    map.iter()
+                        .find(|&&(_, src, len)| src <= x && x < src + len)
+                        .map_or(x, |&(dst, src, _)| dst + x - src)
+                })
+            })
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(5);
+        for size in [1, 3, 10] {
+            let input = gen::day5(&mut rng, size);
+            assert_eq!(part2(&input), brute_force_part2(&input), "{input}");
+        }
+    }
 }
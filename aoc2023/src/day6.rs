@@ -53,9 +53,22 @@ pub fn part2(input: &str) -> usize {
     // j^2-j*t+d=0 ax^2+bx+c=0 => x=(-b±√(b^2-4ac))/(2a)
     // j=(t±√(t^2-4d))/2
 
-    let high = (time as f64 + ((time * time) as f64 - 4.0 * distance as f64).sqrt()) / 2.0;
-    let low = (time as f64 - ((time * time) as f64 - 4.0 * distance as f64).sqrt()) / 2.0;
-    (high.floor() - low.ceil()) as usize + 1
+    // The float roots can land exactly on a tie, or just off for large times, so step from them
+    // to the first and last winning holds
+    let wins = |j: usize| j * (time - j) > distance;
+    let root = ((time * time) as f64 - 4.0 * distance as f64).sqrt();
+    let mut low = ((time as f64 - root) / 2.0).floor() as usize;
+    while low > 0 && wins(low - 1) {
+        low -= 1;
+    }
+    while low <= time / 2 && !wins(low) {
+        low += 1;
+    }
+    if low > time / 2 {
+        return 0;
+    }
+    // Holds are symmetric around time / 2
+    time - 2 * low + 1
 }
 
 #[cfg(test)]
@@ -63,6 +76,8 @@ mod tests {
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
+    use aoc_common::rng::Rng;
 
     const SAMPLE: &str = indoc! {"
         Time:      7  15   30
@@ -78,4 +93,22 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 71503);
     }
+
+    #[test]
+    fn test_against_counting() {
+        let mut rng = Rng::new(6);
+        for size in [1, 1, 2, 2, 2] {
+            let input = gen::day6(&mut rng, size);
+            let [time, distance] = [0, 1].map(|i| {
+                let line = input.lines().nth(i).unwrap();
+                line.split_whitespace()
+                    .skip(1)
+                    .collect::<String>()
+                    .parse::<usize>()
+                    .unwrap()
+            });
+            let wins = (1..time).filter(|&j| j * (time - j) > distance).count();
+            assert_eq!(part2(&input), wins, "{input}");
+        }
+    }
 }
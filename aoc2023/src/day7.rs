@@ -1,143 +1,173 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::{cmp::Reverse, fmt::Display};
 
 use aoc_runner_derive::aoc;
 
-#[aoc(day7, part1)]
-pub fn part1(input: &str) -> usize {
-    let mut counts = HashMap::new();
-    let mut cards = input
-        .lines()
-        .map(|line| {
-            counts.clear();
-            let (card, bit) = line.split_once(" ").unwrap();
-            let card = card.as_bytes();
-            card.iter().for_each(|&c| {
-                *counts.entry(c).or_insert(0usize) += 1;
-            });
-
-            let level = match counts.len() {
-                1 => 0 as usize, // 5
-                2 => {
-                    if counts.values().any(|&v| v == 4) {
-                        1 // 4,1
-                    } else {
-                        2 // 3,2
-                    }
-                }
-                3 => {
-                    if counts.values().any(|&v| v == 3) {
-                        3 // 3,1,1
-                    } else {
-                        4 // 2,2,1
-                    }
-                }
-                4 => 5, // 2,1,1,1
-                5 => 6, // 1,1,1,1,1
-                _ => unreachable!(),
-            };
-            (card, bit.parse::<usize>().unwrap(), level)
-        })
-        .collect::<Vec<_>>();
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
 
-    const CARD_ORDER: &[u8] = b"AKQJT98765432";
-    cards.sort_by(|a, b| {
-        let level_a = a.2;
-        let level_b = b.2;
-        if level_a != level_b {
-            return level_b.cmp(&level_a);
-        }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    // Compare cards in the order they were dealt
+    Dealt,
+    // Compare cards from strongest to weakest, as in poker
+    Sorted,
+}
 
-        for i in 0..a.0.len() {
-            let card_a = a.0[i];
-            let card_b = b.0[i];
-            if card_a != card_b {
-                let index_a = CARD_ORDER.iter().position(|&c| c == card_a).unwrap();
-                let index_b = CARD_ORDER.iter().position(|&c| c == card_b).unwrap();
-                return index_b.cmp(&index_a);
-            }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    // Cards from weakest to strongest
+    pub ranking: &'static [u8],
+    pub wildcard: Option<u8>,
+    pub tie_break: TieBreak,
+}
+
+// The derived order compares the type first, then the tie-break ranks.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hand {
+    hand_type: HandType,
+    ranks: Vec<usize>,
+    natural: HandType,
+    cards: String,
+    wildcard: Option<u8>,
+}
+
+impl Rules {
+    pub const STANDARD: Rules = Rules {
+        ranking: b"23456789TJQKA",
+        wildcard: None,
+        tie_break: TieBreak::Dealt,
+    };
+
+    pub const JOKERS: Rules = Rules {
+        ranking: b"J23456789TQKA",
+        wildcard: Some(b'J'),
+        tie_break: TieBreak::Dealt,
+    };
+
+    fn rank(&self, card: u8) -> usize {
+        self.ranking
+            .iter()
+            .position(|&c| c == card)
+            .unwrap_or_else(|| panic!("unknown card `{}`", card as char))
+    }
+}
+
+impl HandType {
+    // `counts` must be sorted from largest to smallest.
+    fn from_counts(counts: &[usize]) -> Self {
+        match (counts.first(), counts.get(1)) {
+            (Some(5), _) => HandType::FiveOfAKind,
+            (Some(4), _) => HandType::FourOfAKind,
+            (Some(3), Some(2)) => HandType::FullHouse,
+            (Some(3), _) => HandType::ThreeOfAKind,
+            (Some(2), Some(2)) => HandType::TwoPair,
+            (Some(2), _) => HandType::OnePair,
+            _ => HandType::HighCard,
         }
-        Ordering::Equal
-    });
+    }
+}
 
-    cards
-        .iter()
-        .enumerate()
-        .fold(0, |acc, (index, (_card, bit, _level))| {
-            acc + bit * (index + 1)
-        })
+impl Display for HandType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            HandType::HighCard => "high card",
+            HandType::OnePair => "one pair",
+            HandType::TwoPair => "two pair",
+            HandType::ThreeOfAKind => "three of a kind",
+            HandType::FullHouse => "full house",
+            HandType::FourOfAKind => "four of a kind",
+            HandType::FiveOfAKind => "five of a kind",
+        };
+        write!(f, "{name}")
+    }
 }
 
-#[aoc(day7, part2)]
-pub fn part2(input: &str) -> usize {
-    let mut counts = HashMap::new();
-    let mut cards = input
-        .lines()
-        .map(|line| {
-            counts.clear();
-            let (card, bit) = line.split_once(" ").unwrap();
-            let card = card.as_bytes();
-            card.iter().for_each(|&c| {
-                *counts.entry(c).or_insert(0usize) += 1;
-            });
-
-            let j_count = counts.remove(&b'J').unwrap_or(0);
-            let max_count_key = counts
-                .iter()
-                .max_by_key(|&(_, &v)| v)
-                .map(|(&k, _)| k)
-                .unwrap_or(b'J');
-            *counts.entry(max_count_key).or_insert(0) += j_count;
-            let max_count = *counts.get(&max_count_key).unwrap();
-            let level = match counts.len() {
-                1 => 0 as usize, // 5
-                2 => {
-                    if max_count == 4 {
-                        1 // 4,1
-                    } else {
-                        2 // 3,2
-                    }
-                }
-                3 => {
-                    if max_count == 3 {
-                        3 // 3,1,1
-                    } else {
-                        4 // 2,2,1
-                    }
-                }
-                4 => 5, // 2,1,1,1
-                5 => 6, // 1,1,1,1,1
-                _ => unreachable!(),
-            };
-            (card, bit.parse::<usize>().unwrap(), level)
-        })
-        .collect::<Vec<_>>();
+fn counts(cards: impl Iterator<Item = u8>) -> Vec<usize> {
+    let mut counts = [0; 256];
+    for card in cards {
+        counts[card as usize] += 1;
+    }
+    let mut counts = counts.into_iter().filter(|&c| c > 0).collect::<Vec<_>>();
+    counts.sort_unstable_by_key(|&c| Reverse(c));
+    counts
+}
 
-    const CARD_ORDER: &[u8] = b"AKQT98765432J";
-    cards.sort_by(|a, b| {
-        let level_a = a.2;
-        let level_b = b.2;
-        if level_a != level_b {
-            return level_b.cmp(&level_a);
+impl Hand {
+    pub fn new(cards: &str, rules: &Rules) -> Self {
+        let natural = HandType::from_counts(&counts(cards.bytes()));
+
+        // Wildcards always do best joining the largest group
+        let wilds = cards.bytes().filter(|&c| Some(c) == rules.wildcard).count();
+        let mut grouped = counts(cards.bytes().filter(|&c| Some(c) != rules.wildcard));
+        match grouped.first_mut() {
+            Some(largest) => *largest += wilds,
+            None => grouped.push(wilds),
+        }
+
+        let mut ranks = cards.bytes().map(|c| rules.rank(c)).collect::<Vec<_>>();
+        if rules.tie_break == TieBreak::Sorted {
+            ranks.sort_unstable_by_key(|&r| Reverse(r));
+        }
+
+        Hand {
+            hand_type: HandType::from_counts(&grouped),
+            ranks,
+            natural,
+            cards: cards.to_string(),
+            wildcard: rules.wildcard,
         }
+    }
+
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
 
-        for i in 0..a.0.len() {
-            let card_a = a.0[i];
-            let card_b = b.0[i];
-            if card_a != card_b {
-                let index_a = CARD_ORDER.iter().position(|&c| c == card_a).unwrap();
-                let index_b = CARD_ORDER.iter().position(|&c| c == card_b).unwrap();
-                return index_b.cmp(&index_a);
+    pub fn cards(&self) -> &str {
+        &self.cards
+    }
+
+    pub fn explain(&self) -> String {
+        match self.wildcard {
+            Some(wildcard) if self.hand_type != self.natural => {
+                format!("{} via {}", self.hand_type, wildcard as char)
             }
+            _ => self.hand_type.to_string(),
         }
-        Ordering::Equal
-    });
+    }
+}
 
-    cards
+pub fn winnings(input: &str, rules: &Rules) -> usize {
+    let mut hands = input
+        .lines()
+        .map(|line| {
+            let (cards, bid) = line.split_once(' ').unwrap();
+            (Hand::new(cards, rules), bid.parse::<usize>().unwrap())
+        })
+        .collect::<Vec<_>>();
+    hands.sort();
+    hands
         .iter()
         .enumerate()
-        .fold(0, |acc, (index, (_card, bit, _level))| {
-            acc + bit * (index + 1)
-        })
+        .map(|(index, (_, bid))| (index + 1) * bid)
+        .sum()
+}
+
+#[aoc(day7, part1)]
+pub fn part1(input: &str) -> usize {
+    winnings(input, &Rules::STANDARD)
+}
+
+#[aoc(day7, part2)]
+pub fn part2(input: &str) -> usize {
+    winnings(input, &Rules::JOKERS)
 }
 
 #[cfg(test)]
@@ -163,4 +193,44 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 5905);
     }
+
+    #[test]
+    fn test_explain() {
+        let explain = |cards, rules| Hand::new(cards, rules).explain();
+        assert_eq!(explain("T55J5", &Rules::STANDARD), "three of a kind");
+        assert_eq!(explain("T55J5", &Rules::JOKERS), "four of a kind via J");
+        assert_eq!(explain("KTJJT", &Rules::JOKERS), "four of a kind via J");
+        assert_eq!(explain("2233J", &Rules::JOKERS), "full house via J");
+        assert_eq!(explain("JJJJJ", &Rules::JOKERS), "five of a kind");
+        assert_eq!(explain("32T3K", &Rules::JOKERS), "one pair");
+        assert_eq!(
+            Hand::new("JJJJJ", &Rules::JOKERS).hand_type(),
+            HandType::FiveOfAKind
+        );
+    }
+
+    #[test]
+    fn test_rules() {
+        let jokers = Hand::new("JKKK2", &Rules::JOKERS);
+        let queens = Hand::new("QQQQ2", &Rules::JOKERS);
+        assert!(jokers < queens);
+        assert!(Hand::new("JKKK2", &Rules::STANDARD) < Hand::new("QQQQ2", &Rules::STANDARD));
+
+        let sorted = Rules {
+            tie_break: TieBreak::Sorted,
+            ..Rules::STANDARD
+        };
+        assert!(Hand::new("2AAK3", &Rules::STANDARD) < Hand::new("A2AQ3", &Rules::STANDARD));
+        assert!(Hand::new("2AAK3", &sorted) > Hand::new("A2AQ3", &sorted));
+
+        let deuces = Rules {
+            ranking: b"23456789TJQKA",
+            wildcard: Some(b'2'),
+            tie_break: TieBreak::Dealt,
+        };
+        assert_eq!(
+            Hand::new("2AK22", &deuces).explain(),
+            "four of a kind via 2"
+        );
+    }
 }
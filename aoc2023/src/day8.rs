@@ -1,96 +1,181 @@
 use std::collections::HashMap;
 
+use aoc_common::math::{crt, lcm};
 use aoc_runner_derive::aoc;
 
-#[aoc(day8, part1)]
-pub fn part1(input: &str) -> usize {
-    let (instructions, networks) = input.split_once("\n\n").unwrap();
-    let instructions = instructions
-        .as_bytes()
-        .iter()
-        .map(|&b| if b == b'L' { 0 as usize } else { 1 })
-        .collect::<Vec<_>>();
-    let networks = networks
-        .lines()
-        .map(|line| {
-            let (name, value) = line.split_once(" = ").unwrap();
-            let value = value
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split(", ")
-                .collect::<Vec<_>>();
-            (name, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
-
-    let mut value = "AAA";
-    let target = "ZZZ";
-    let mut steps = 0usize;
-    loop {
-        for &instruction in &instructions {
+pub struct Map<'a> {
+    instructions: Vec<usize>,
+    names: Vec<&'a str>,
+    next: Vec<[usize; 2]>,
+}
+
+// The steps at which a ghost stands on an end node. After `cycle_start` steps
+// the ghost repeats every `cycle_len` steps, so `cycle_hits` (all in
+// `cycle_start..cycle_start + cycle_len`) recur forever while `prefix_hits`
+// happen once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ghost {
+    pub prefix_hits: Vec<u64>,
+    pub cycle_start: u64,
+    pub cycle_len: u64,
+    pub cycle_hits: Vec<u64>,
+}
+
+impl<'a> Map<'a> {
+    pub fn from(input: &'a str) -> Self {
+        let (instructions, network) = input.split_once("\n\n").unwrap();
+        let instructions = instructions
+            .bytes()
+            .map(|b| if b == b'L' { 0 } else { 1 })
+            .collect();
+        let nodes = network
+            .lines()
+            .map(|line| {
+                let (name, value) = line.split_once(" = ").unwrap();
+                let (left, right) = value
+                    .trim_start_matches('(')
+                    .trim_end_matches(')')
+                    .split_once(", ")
+                    .unwrap();
+                (name, left, right)
+            })
+            .collect::<Vec<_>>();
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, &(name, ..))| (name, i))
+            .collect::<HashMap<_, _>>();
+        Map {
+            instructions,
+            names: nodes.iter().map(|&(name, ..)| name).collect(),
+            next: nodes
+                .iter()
+                .map(|(_, left, right)| [index[left], index[right]])
+                .collect(),
+        }
+    }
+
+    fn index(&self, name: &str) -> usize {
+        self.names.iter().position(|&n| n == name).unwrap()
+    }
+
+    pub fn steps(&self, from: &str, to: &str) -> usize {
+        let (mut node, to) = (self.index(from), self.index(to));
+        let mut steps = 0;
+        while steps == 0 || node != to {
+            node = self.next[node][self.instructions[steps % self.instructions.len()]];
             steps += 1;
-            value = networks[value][instruction];
-            if value == target {
-                return steps;
+        }
+        steps
+    }
+
+    // Walks until a (node, instruction index) state repeats.
+    pub fn ghost(&self, start: usize, is_end: impl Fn(&str) -> bool) -> Ghost {
+        let len = self.instructions.len();
+        let mut seen = vec![u64::MAX; self.names.len() * len];
+        let mut hits = vec![];
+        let (mut node, mut steps) = (start, 0u64);
+        loop {
+            let state = node * len + steps as usize % len;
+            if seen[state] != u64::MAX {
+                let cycle_start = seen[state];
+                let split = hits.partition_point(|&hit| hit < cycle_start);
+                let cycle_hits = hits.split_off(split);
+                return Ghost {
+                    prefix_hits: hits,
+                    cycle_start,
+                    cycle_len: steps - cycle_start,
+                    cycle_hits,
+                };
             }
+            seen[state] = steps;
+            if steps > 0 && is_end(self.names[node]) {
+                hits.push(steps);
+            }
+            node = self.next[node][self.instructions[steps as usize % len]];
+            steps += 1;
         }
     }
 }
 
-#[aoc(day8, part2)]
-pub fn part2(input: &str) -> usize {
-    let (instructions, networks) = input.split_once("\n\n").unwrap();
-    let instructions = instructions
-        .as_bytes()
-        .iter()
-        .map(|&b| if b == b'L' { 0 as usize } else { 1 })
-        .collect::<Vec<_>>();
-    let networks = networks
-        .lines()
-        .map(|line| {
-            let (name, value) = line.split_once(" = ").unwrap();
-            let value = value
-                .trim_start_matches('(')
-                .trim_end_matches(')')
-                .split(", ")
-                .collect::<Vec<_>>();
-            (name, value)
-        })
-        .collect::<HashMap<&str, Vec<&str>>>();
-
-    let cal_steps =
-        |value: &str, networks: &HashMap<&str, Vec<&str>>, instructions: &Vec<usize>| {
-            let mut steps = 0;
-            let mut value = value;
-            loop {
-                for &instruction in instructions {
-                    steps += 1;
-                    value = networks[value][instruction];
-                    if value.ends_with("Z") {
-                        return steps;
-                    }
-                }
-            }
-        };
+impl Ghost {
+    pub fn hits_at(&self, steps: u64) -> bool {
+        if steps < self.cycle_start {
+            return self.prefix_hits.contains(&steps);
+        }
+        let offset = (steps - self.cycle_start) % self.cycle_len;
+        self.cycle_hits.contains(&(self.cycle_start + offset))
+    }
 
-    let values = networks
-        .keys()
-        .filter(|&key| key.ends_with("A"))
-        .collect::<Vec<_>>();
-    values
-        .iter()
-        .map(|value| cal_steps(value, &networks, &instructions))
-        .fold(1, |answers, steps| (answers * steps) / gcd(answers, steps))
+    // The puzzle inputs hit an end node exactly once per cycle, at a multiple
+    // of the cycle length, so the hits are just the multiples of that length.
+    fn is_simple(&self) -> bool {
+        self.prefix_hits.is_empty() && self.cycle_hits == [self.cycle_len]
+    }
 }
 
-fn gcd(a: usize, b: usize) -> usize {
-    if b == 0 {
-        a
-    } else {
-        gcd(b, a % b)
+// The first step at which every ghost is on an end node, if there is one.
+pub fn first_common_hit(ghosts: &[Ghost]) -> Option<u64> {
+    if ghosts.iter().all(Ghost::is_simple) {
+        return Some(ghosts.iter().map(|g| g.cycle_len).fold(1, lcm));
+    }
+
+    // Before every ghost is cycling, the answer is one of the prefix hits of
+    // the ghost that starts cycling last.
+    let latest = ghosts.iter().max_by_key(|g| g.cycle_start)?;
+    if let Some(&hit) = latest
+        .prefix_hits
+        .iter()
+        .find(|&&hit| ghosts.iter().all(|g| g.hits_at(hit)))
+    {
+        return Some(hit);
+    }
+
+    // Afterwards, try each combination of one cycle hit per ghost.
+    let from = latest.cycle_start.max(1);
+    let mut best = None;
+    let mut choice = vec![0; ghosts.len()];
+    loop {
+        let congruences = ghosts
+            .iter()
+            .zip(&choice)
+            .map(|(g, &c)| {
+                let hit = g.cycle_hits.get(c)?;
+                Some((hit % g.cycle_len, g.cycle_len))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if let Some((residue, modulus)) = crt(congruences) {
+            let steps = residue + from.saturating_sub(residue).div_ceil(modulus) * modulus;
+            best = Some(best.map_or(steps, |best: u64| best.min(steps)));
+        }
+
+        let mut i = 0;
+        while i < ghosts.len() && choice[i] + 1 == ghosts[i].cycle_hits.len() {
+            choice[i] = 0;
+            i += 1;
+        }
+        if i == ghosts.len() {
+            return best;
+        }
+        choice[i] += 1;
     }
 }
 
+#[aoc(day8, part1)]
+pub fn part1(input: &str) -> usize {
+    Map::from(input).steps("AAA", "ZZZ")
+}
+
+#[aoc(day8, part2)]
+pub fn part2(input: &str) -> u64 {
+    let map = Map::from(input);
+    let ghosts = (0..map.names.len())
+        .filter(|&node| map.names[node].ends_with('A'))
+        .map(|start| map.ghost(start, |name| name.ends_with('Z')))
+        .collect::<Vec<_>>();
+    first_common_hit(&ghosts).expect("ghosts never line up")
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -127,4 +212,65 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE_2), 6);
     }
+
+    fn brute_force(input: &str) -> u64 {
+        let map = Map::from(input);
+        let mut nodes = (0..map.names.len())
+            .filter(|&node| map.names[node].ends_with('A'))
+            .collect::<Vec<_>>();
+        for steps in 0.. {
+            let instruction = map.instructions[steps % map.instructions.len()];
+            for node in &mut nodes {
+                *node = map.next[*node][instruction];
+            }
+            if nodes.iter().all(|&node| map.names[node].ends_with('Z')) {
+                return steps as u64 + 1;
+            }
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_offsets_and_multiple_hits() {
+        let input = indoc! {"
+            L
+
+            11A = (11B, 11B)
+            11B = (11Z, 11Z)
+            11Z = (11B, 11B)
+            22A = (22Z, 22Z)
+            22Z = (22B, 22B)
+            22B = (22C, 22C)
+            22C = (22Z, 22Z)
+            33A = (33B, 33B)
+            33B = (34Z, 34Z)
+            34Z = (33C, 33C)
+            33C = (33D, 33D)
+            33D = (35Z, 35Z)
+            35Z = (33B, 33B)
+        "};
+        let map = Map::from(input);
+        let ghost = map.ghost(map.index("33A"), |name| name.ends_with('Z'));
+        assert_eq!(ghost.cycle_start, 1);
+        assert_eq!(ghost.cycle_len, 5);
+        assert_eq!(ghost.cycle_hits, [2, 5]);
+        assert_eq!(part2(input), 10);
+        assert_eq!(brute_force(input), 10);
+    }
+
+    #[test]
+    fn test_prefix_hits() {
+        let input = indoc! {"
+            LR
+
+            11A = (11Z, 11Z)
+            11Z = (11B, 11B)
+            11B = (11B, 11B)
+            22A = (22Z, 22Z)
+            22Z = (22Z, 22Z)
+        "};
+        assert_eq!(part2(input), 1);
+        assert_eq!(brute_force(input), 1);
+        assert_eq!(part2(SAMPLE_2), brute_force(SAMPLE_2));
+    }
 }
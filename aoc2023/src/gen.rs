@@ -0,0 +1,720 @@
+use aoc_common::rng::Rng;
+
+fn grid(rng: &mut Rng, height: usize, width: usize, cell: impl Fn(&mut Rng) -> char) -> String {
+    (0..height)
+        .map(|_| (0..width).map(|_| cell(rng)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    let words = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "x", "q", "z",
+    ];
+    join_lines((0..size).map(|_| {
+        let mut line = String::new();
+        for _ in 0..rng.below(4) {
+            let word = *rng.choose(&words);
+            line.push_str(word);
+        }
+        line.push(char::from(b'1' + rng.below(9) as u8));
+        for _ in 0..rng.below(4) {
+            if rng.chance(0.3) {
+                line.push(char::from(b'1' + rng.below(9) as u8));
+            } else {
+                let word = *rng.choose(&words);
+                line.push_str(word);
+            }
+        }
+        line
+    }))
+}
+
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    let colors = ["red", "green", "blue"];
+    join_lines((1..=size).map(|id| {
+        let sets = (0..1 + rng.below(5))
+            .map(|_| {
+                let mut cubes = colors.to_vec();
+                rng.shuffle(&mut cubes);
+                cubes.truncate(1 + rng.below(3));
+                cubes
+                    .iter()
+                    .map(|color| format!("{} {color}", rng.range(1..=20)))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .collect::<Vec<_>>();
+        format!("Game {id}: {}", sets.join("; "))
+    }))
+}
+
+// Rows are built from whole tokens with a gap after every number, so numbers
+// never run together into something too long for usize.
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let width = size.max(5);
+    join_lines((0..size.max(5)).map(|_| {
+        let mut row = String::new();
+        while row.len() < width {
+            match rng.below(4) {
+                0 => {
+                    row.push_str(&rng.range(1..=999).to_string());
+                    row.push('.');
+                }
+                1 => row.push(*rng.choose(&['*', '#', '+', '$', '/', '='])),
+                _ => row.push('.'),
+            }
+        }
+        row.truncate(width);
+        row
+    }))
+}
+
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    join_lines((1..=size).map(|id| {
+        let mut numbers = (1..=99).collect::<Vec<_>>();
+        rng.shuffle(&mut numbers);
+        let winning = numbers[..5].iter().map(|n| format!("{n:>2}"));
+        let mut have = numbers[..10].to_vec();
+        rng.shuffle(&mut have);
+        let have = have[..8].iter().map(|n| format!("{n:>2}"));
+        format!(
+            "Card {id:>3}: {} | {}",
+            winning.collect::<Vec<_>>().join(" "),
+            have.collect::<Vec<_>>().join(" ")
+        )
+    }))
+}
+
+// Each map splits the values below 1000 into consecutive source ranges, so no
+// two lines of a map overlap.
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let kinds = [
+        "seed",
+        "soil",
+        "fertilizer",
+        "water",
+        "light",
+        "temperature",
+        "humidity",
+        "location",
+    ];
+    let seeds = (0..2 * size.clamp(1, 10))
+        .map(|_| rng.range(0..=500).to_string())
+        .collect::<Vec<_>>();
+    let maps = kinds.windows(2).map(|pair| {
+        let mut lines = vec![];
+        let mut start = 0;
+        while start < 1000 {
+            let len = rng.range(1..=200);
+            if rng.chance(0.7) {
+                lines.push(format!("{} {start} {len}", rng.range(0..=1000)));
+            }
+            start += len;
+        }
+        rng.shuffle(&mut lines);
+        format!("{}-to-{} map:\n{}", pair[0], pair[1], lines.join("\n"))
+    });
+    format!(
+        "seeds: {}\n\n{}",
+        seeds.join(" "),
+        maps.collect::<Vec<_>>().join("\n\n")
+    )
+}
+
+// Part 2 reads the races as one concatenated race, so two-digit records below
+// two-digit times keep that race winnable and within usize.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    let races = (0..size.clamp(1, 4))
+        .map(|_| {
+            let time = rng.range(20..=99);
+            let record = rng.range(10..=99);
+            (time, record)
+        })
+        .collect::<Vec<_>>();
+    let column = |values: Vec<i64>| values.iter().map(|v| format!("{v:>4}")).collect::<String>();
+    format!(
+        "Time:    {}\nDistance:{}",
+        column(races.iter().map(|r| r.0).collect()),
+        column(races.iter().map(|r| r.1).collect())
+    )
+}
+
+pub fn day7(rng: &mut Rng, size: usize) -> String {
+    let cards = [
+        '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+    ];
+    join_lines((0..size).map(|_| {
+        let hand = (0..5).map(|_| *rng.choose(&cards)).collect::<String>();
+        format!("{hand} {}", rng.range(1..=1000))
+    }))
+}
+
+// Each ghost walks its own cycle back to the node after its start, reaching
+// its `Z` node at the end of every lap, like the real inputs. Both branches
+// of a node lead the same way, so the instructions don't matter.
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    let instructions = (0..1 + rng.below(size.max(1)))
+        .map(|_| *rng.choose(&['L', 'R']))
+        .collect::<String>();
+    let mut nodes = vec![];
+    for ghost in 0..2 + rng.below(3) {
+        let c = char::from(b'A' + ghost as u8);
+        let len = 2 + rng.below(11);
+        let mut names = vec![format!("{c}{c}A")];
+        names.extend((1..len).map(|k| format!("{c}{k:02}")));
+        names.push(if ghost == 0 {
+            String::from("ZZZ")
+        } else {
+            format!("{c}{c}Z")
+        });
+        for (i, name) in names.iter().enumerate() {
+            let next = &names[if i + 1 < names.len() { i + 1 } else { 1 }];
+            nodes.push(format!("{name} = ({next}, {next})"));
+        }
+    }
+    rng.shuffle(&mut nodes);
+    format!("{instructions}\n\n{}", join_lines(nodes))
+}
+
+pub fn day9(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        let degree = rng.below(4);
+        let coefficients = (0..=degree).map(|_| rng.range(-5..=5)).collect::<Vec<_>>();
+        (0..degree as i64 + 6)
+            .map(|x| {
+                coefficients
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, &c| acc * x + c)
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }))
+}
+
+// The loop traces the outline of a histogram: a run of columns of random
+// height standing on a common bottom row. The tiles around it are random
+// pipes, except next to `S`, where they could connect to it.
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    let (height, width) = (size.max(4), size.max(4));
+    let bottom = height - 2;
+    let mut corners = vec![];
+    let mut col = 1;
+    while col < width - 2 {
+        let top = 1 + rng.below(bottom - 1);
+        let next = (col + 1 + rng.below(4)).min(width - 2);
+        corners.push((top, col));
+        corners.push((top, next));
+        col = next;
+    }
+    corners.push((bottom, col));
+    corners.push((bottom, 1));
+
+    let mut path = vec![];
+    let mut pos = *corners.last().unwrap();
+    for &corner in &corners {
+        while pos != corner {
+            pos.0 = (pos.0 as isize + (corner.0 as isize - pos.0 as isize).signum()) as usize;
+            pos.1 = (pos.1 as isize + (corner.1 as isize - pos.1 as isize).signum()) as usize;
+            path.push(pos);
+        }
+    }
+
+    let pipes = ['|', '-', 'L', 'J', '7', 'F', '.'];
+    let mut grid = (0..height)
+        .map(|_| (0..width).map(|_| *rng.choose(&pipes)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    for (i, &(row, col)) in path.iter().enumerate() {
+        let prev = path[(i + path.len() - 1) % path.len()];
+        let next = path[(i + 1) % path.len()];
+        let toward = |(r, c): (usize, usize)| match (r.cmp(&row), c.cmp(&col)) {
+            (std::cmp::Ordering::Less, _) => 'N',
+            (std::cmp::Ordering::Greater, _) => 'S',
+            (_, std::cmp::Ordering::Less) => 'W',
+            _ => 'E',
+        };
+        let mut ends = [toward(prev), toward(next)];
+        ends.sort_unstable();
+        grid[row][col] = match ends {
+            ['N', 'S'] => '|',
+            ['E', 'W'] => '-',
+            ['E', 'N'] => 'L',
+            ['N', 'W'] => 'J',
+            ['S', 'W'] => '7',
+            _ => 'F',
+        };
+    }
+
+    let (row, col) = *rng.choose(&path);
+    grid[row][col] = 'S';
+    for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        let (r, c) = ((row as isize + dr) as usize, (col as isize + dc) as usize);
+        if !path.contains(&(r, c)) {
+            grid[r][c] = '.';
+        }
+    }
+    join_lines(grid.into_iter().map(|row| row.into_iter().collect()))
+}
+
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    grid(
+        rng,
+        size,
+        size,
+        |rng| if rng.chance(0.1) { '#' } else { '.' },
+    )
+}
+
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        let mut springs = (0..1 + rng.below(12))
+            .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+            .collect::<Vec<_>>();
+        let len = springs.len();
+        springs[rng.below(len)] = '#';
+        let groups = springs
+            .split(|&c| c == '.')
+            .filter(|group| !group.is_empty())
+            .map(|group| group.len().to_string())
+            .collect::<Vec<_>>();
+        let springs = springs
+            .iter()
+            .map(|&c| if rng.chance(0.5) { '?' } else { c })
+            .collect::<String>();
+        format!("{springs} {}", groups.join(","))
+    }))
+}
+
+// Every pattern mirrors exactly across one line and has one smudge across
+// another: its columns reflect around a line near the left edge, leaving a
+// free column on the right, and a cell in that column breaks an otherwise
+// exact row reflection. Half of the patterns are transposed.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    (0..size.max(1))
+        .map(|_| {
+            let (height, width) = (4 + rng.below(8), 4 + rng.below(8));
+            let mut grid = (0..height)
+                .map(|_| {
+                    (0..width)
+                        .map(|_| if rng.chance(0.5) { '#' } else { '.' })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let axis = rng.below((width - 1) / 2);
+            for row in &mut grid {
+                for d in 0..=axis {
+                    row[axis + 1 + d] = row[axis - d];
+                }
+            }
+            let axis = rng.below(height - 1);
+            let reach = (axis + 1).min(height - axis - 1);
+            for d in 0..reach {
+                grid[axis + 1 + d] = grid[axis - d].clone();
+            }
+            let row = axis - rng.below(reach);
+            grid[row][width - 1] = if grid[row][width - 1] == '#' {
+                '.'
+            } else {
+                '#'
+            };
+
+            if rng.chance(0.5) {
+                grid = (0..width)
+                    .map(|col| grid.iter().map(|row| row[col]).collect())
+                    .collect();
+            }
+            join_lines(grid.into_iter().map(|row| row.into_iter().collect()))
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| match rng.below(5) {
+        0 => 'O',
+        1 => '#',
+        _ => '.',
+    })
+}
+
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    let letters = ['a', 'b', 'c', 'd'];
+    (0..size)
+        .map(|_| {
+            let label = (0..1 + rng.below(2))
+                .map(|_| *rng.choose(&letters))
+                .collect::<String>();
+            if rng.chance(0.3) {
+                format!("{label}-")
+            } else {
+                format!("{label}={}", rng.range(1..=9))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+pub fn day16(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| {
+        if rng.chance(0.2) {
+            *rng.choose(&['/', '\\', '|', '-'])
+        } else {
+            '.'
+        }
+    })
+}
+
+pub fn day17(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| char::from(b'1' + rng.below(9) as u8))
+}
+
+// The outline of a histogram: a run of columns of random height, walked
+// clockwise from the top-left corner. Directions are 0 = R, 1 = D, 2 = L, 3 = U.
+fn outline(rng: &mut Rng, columns: usize, max: i64) -> Vec<(usize, i64)> {
+    let start = rng.range(1..=max);
+    let (mut height, mut total) = (start, 0);
+    let mut moves = vec![];
+    for i in 0..columns {
+        if i > 0 {
+            let mut next = rng.range(1..=max);
+            if next == height {
+                next += 1;
+            }
+            moves.push(if next > height {
+                (3, next - height)
+            } else {
+                (1, height - next)
+            });
+            height = next;
+        }
+        let width = rng.range(1..=max);
+        total += width;
+        moves.push((0, width));
+    }
+    moves.extend([(1, height), (2, total), (3, start)]);
+    moves
+}
+
+// The short instructions and the colors describe two unrelated outlines with
+// the same number of moves.
+pub fn day18(rng: &mut Rng, size: usize) -> String {
+    let columns = 1 + rng.below(size.max(1));
+    let short = outline(rng, columns, 10);
+    let long = outline(rng, columns, 0xfffff / (columns as i64 + 1));
+    join_lines(
+        short
+            .iter()
+            .zip(&long)
+            .map(|(&(dir, dist), &(color, length))| {
+                format!(
+                    "{} {dist} (#{length:05x}{color})",
+                    ["R", "D", "L", "U"][dir]
+                )
+            }),
+    )
+}
+
+// Workflows only send parts to later workflows, so there are no cycles.
+pub fn day19(rng: &mut Rng, size: usize) -> String {
+    let count = size.max(1);
+    let names = (0..count)
+        .map(|i| {
+            if i == 0 {
+                String::from("in")
+            } else {
+                format!(
+                    "q{}{}",
+                    char::from(b'a' + (i / 26) as u8),
+                    char::from(b'a' + (i % 26) as u8)
+                )
+            }
+        })
+        .collect::<Vec<_>>();
+    let target = |rng: &mut Rng, i: usize| {
+        if i + 1 < count && rng.chance(0.6) {
+            names[i + 1 + rng.below(count - i - 1)].clone()
+        } else {
+            String::from(*rng.choose(&["A", "R"]))
+        }
+    };
+    let workflows = (0..count).map(|i| {
+        let mut rules = (0..1 + rng.below(3))
+            .map(|_| {
+                format!(
+                    "{}{}{}:{}",
+                    *rng.choose(&['x', 'm', 'a', 's']),
+                    *rng.choose(&['<', '>']),
+                    rng.range(1..=4000),
+                    target(rng, i)
+                )
+            })
+            .collect::<Vec<_>>();
+        rules.push(target(rng, i));
+        format!("{}{{{}}}", names[i], rules.join(","))
+    });
+    let workflows = workflows.collect::<Vec<_>>();
+    let parts = (0..count).map(|_| {
+        format!(
+            "{{x={},m={},a={},s={}}}",
+            rng.range(1..=4000),
+            rng.range(1..=4000),
+            rng.range(1..=4000),
+            rng.range(1..=4000)
+        )
+    });
+    format!("{}\n\n{}", join_lines(workflows), join_lines(parts))
+}
+
+// Counters shaped like the real inputs, with `rx` fed through one inverter
+// per counter.
+pub fn counter_network(periods: &[usize]) -> String {
+    let mut lines = vec![];
+    let mut starts = vec![];
+    for (c, &period) in periods.iter().enumerate() {
+        let len = usize::BITS - period.leading_zeros();
+        let mut hub = vec![];
+        for i in 0..len {
+            let mut outputs = vec![];
+            if i + 1 < len {
+                outputs.push(format!("c{c}b{}", i + 1));
+            }
+            if period & (1 << i) != 0 {
+                outputs.push(format!("h{c}"));
+            }
+            if i == 0 || period & (1 << i) == 0 {
+                hub.push(format!("c{c}b{i}"));
+            }
+            lines.push(format!("%c{c}b{i} -> {}", outputs.join(", ")));
+        }
+        hub.push(format!("i{c}"));
+        lines.push(format!("&h{c} -> {}", hub.join(", ")));
+        lines.push(format!("&i{c} -> f"));
+        starts.push(format!("c{c}b0"));
+    }
+    lines.push(String::from("&f -> rx"));
+    lines.push(format!("broadcaster -> {}", starts.join(", ")));
+    lines.join("\n")
+}
+
+pub fn day20(rng: &mut Rng, size: usize) -> String {
+    let periods = (0..2 + size % 3)
+        .map(|_| rng.range(2048..=4095) as usize | 1)
+        .collect::<Vec<_>>();
+    counter_network(&periods)
+}
+
+// A square garden with the start in the middle and its row, column and border
+// left open, as in the real inputs.
+pub fn day21(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(5) / 2 * 2 + 1;
+    let mut garden = vec![vec!['.'; side]; side];
+    for (r, row) in garden.iter_mut().enumerate() {
+        for (c, tile) in row.iter_mut().enumerate() {
+            let lane = [0, side / 2, side - 1];
+            if !lane.contains(&r) && !lane.contains(&c) && rng.chance(0.15) {
+                *tile = '#';
+            }
+        }
+    }
+    garden[side / 2][side / 2] = 'S';
+    join_lines(garden.iter().map(|row| row.iter().collect()))
+}
+
+// Every brick gets its own band of heights, so none of them overlap in the
+// snapshot.
+pub fn day22(rng: &mut Rng, size: usize) -> String {
+    let mut z = 1;
+    let mut bricks = (0..size.max(1))
+        .map(|_| {
+            let (x, y) = (rng.range(0..=9), rng.range(0..=9));
+            let len = rng.range(0..=3);
+            let (dx, dy, dz) = match rng.below(3) {
+                0 => (len.min(9 - x), 0, 0),
+                1 => (0, len.min(9 - y), 0),
+                _ => (0, 0, len),
+            };
+            let brick = format!("{x},{y},{z}~{},{},{}", x + dx, y + dy, z + dz);
+            z += dz + 1 + rng.below(2) as i64;
+            brick
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut bricks);
+    join_lines(bricks)
+}
+
+// A maze carved over the odd cells with extra walls knocked out in proportion
+// to its area, so the number of junctions, and of routes between the top-left
+// entrance and the bottom-right exit, grows with `size` as in the real inputs.
+pub fn day23(rng: &mut Rng, size: usize) -> String {
+    let cells = size.max(2) / 2;
+    let side = cells * 2 + 1;
+    let mut maze = vec![vec!['#'; side]; side];
+
+    let mut stack = vec![(1, 1)];
+    maze[1][1] = '.';
+    while let Some(&(r, c)) = stack.last() {
+        let mut moves = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .into_iter()
+            .map(|(dr, dc)| ((r as isize + dr) as usize, (c as isize + dc) as usize))
+            .filter(|&(rr, cc)| rr < side && cc < side && maze[rr][cc] == '#')
+            .collect::<Vec<_>>();
+        if moves.is_empty() {
+            stack.pop();
+            continue;
+        }
+        rng.shuffle(&mut moves);
+        let (rr, cc) = moves[0];
+        maze[(r + rr) / 2][(c + cc) / 2] = '.';
+        maze[rr][cc] = '.';
+        stack.push((rr, cc));
+    }
+
+    for _ in 0..cells * cells {
+        let r = 1 + rng.below(side - 2);
+        let c = 1 + rng.below(side - 2);
+        if (r + c) % 2 == 1 {
+            maze[r][c] = '.';
+        }
+    }
+    maze[0][1] = '.';
+    maze[side - 1][side - 2] = '.';
+
+    join_lines(maze.iter().map(|row| row.iter().collect()))
+}
+
+// Every hailstone is placed so that a rock thrown from a known spot hits it at
+// a whole-numbered time, as in the real inputs.
+pub fn day24(rng: &mut Rng, size: usize) -> String {
+    let rock = [0; 6].map(|_| rng.range(-1000..=1000));
+    let mut times = (1..=10 * size.max(3) as i64).collect::<Vec<_>>();
+    rng.shuffle(&mut times);
+    join_lines(times[..size.max(3)].iter().map(|&t| {
+        let velocity = [3, 4, 5].map(|i| rock[i] + rng.range(-50..=50));
+        let position = [0, 1, 2].map(|i| rock[i] + (rock[i + 3] - velocity[i]) * t);
+        format!(
+            "{}, {}, {} @ {}, {}, {}",
+            position[0], position[1], position[2], velocity[0], velocity[1], velocity[2]
+        )
+    }))
+}
+
+// Two densely wired halves joined by three wires, so the cut is the only one
+// of that size.
+pub fn day25(rng: &mut Rng, size: usize) -> String {
+    let mut names = (0..26 * 26 * 26)
+        .map(|i| {
+            String::from_utf8(vec![
+                b'a' + (i / 676) as u8,
+                b'a' + (i / 26 % 26) as u8,
+                b'a' + (i % 26) as u8,
+            ])
+            .unwrap()
+        })
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut names);
+
+    let half = size.max(8);
+    let mut lines = vec![];
+    for side in [0, half] {
+        for i in 0..half {
+            let others = (1..=3)
+                .map(|j| names[side + (i + j) % half].as_str())
+                .collect::<Vec<_>>();
+            lines.push(format!("{}: {}", names[side + i], others.join(" ")));
+        }
+    }
+    for i in 0..3 {
+        lines.push(format!("{}: {}", names[i], names[half + i]));
+    }
+    rng.shuffle(&mut lines);
+    join_lines(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+
+    // Only checks that every solver accepts what the generators make. The
+    // answers are compared against reference solutions in the days' own tests.
+    #[test]
+    fn test_generated_inputs_smoke() {
+        let mut rng = Rng::new(2023);
+        for size in [5, 20] {
+            let input = day1(&mut rng, size);
+            let _ = (day1::part1(&input), day1::part2(&input));
+            let input = day2(&mut rng, size);
+            let _ = (day2::part1(&input), day2::part2(&input));
+            let input = day3(&mut rng, size);
+            let _ = (day3::part1(&input), day3::part2(&input));
+            let input = day4(&mut rng, size);
+            let _ = (day4::part1(&input), day4::part2(&input));
+            let input = day5(&mut rng, size);
+            let _ = (day5::part1(&input), day5::part2(&input));
+            let input = day6(&mut rng, size);
+            let _ = (day6::part1(&input), day6::part2(&input));
+            let input = day7(&mut rng, size);
+            let _ = (day7::part1(&input), day7::part2(&input));
+            let input = day8(&mut rng, size);
+            let _ = (day8::part1(&input), day8::part2(&input));
+            let input = day9(&mut rng, size);
+            let _ = (day9::part1(&input), day9::part2(&input));
+            let input = day10(&mut rng, size);
+            let _ = (day10::part1(&input), day10::part2(&input));
+            let input = day11(&mut rng, size);
+            let _ = (day11::part1(&input), day11::part2(&input));
+            let input = day12(&mut rng, size);
+            let _ = (
+                day12::part1(&input),
+                day12::part2_with(&input, &day12::Params::default()),
+            );
+            let input = day13(&mut rng, size);
+            let _ = (day13::part1(&input), day13::part2(&input));
+            let input = day14(&mut rng, size);
+            let _ = (day14::part1(&input), day14::part2(&input));
+            let input = day15(&mut rng, size);
+            let _ = (day15::part1(&input), day15::part2(&input));
+            let input = day16(&mut rng, size);
+            let _ = (day16::part1(&input), day16::part2(&input));
+            let input = day17(&mut rng, size);
+            let _ = (day17::part1(&input), day17::part2(&input));
+            let input = day18(&mut rng, size);
+            let _ = (day18::part1(&input), day18::part2(&input));
+            let input = day19(&mut rng, size);
+            let _ = (day19::part1(&input), day19::part2(&input));
+            let input = day20(&mut rng, size);
+            let _ = (day20::part1(&input), day20::part2(&input));
+            let input = day21(&mut rng, size);
+            let side = size.max(5) / 2 * 2 + 1;
+            let params = day21::Params {
+                steps: size,
+                infinite_steps: side * 4 + side / 2,
+            };
+            let _ = (
+                day21::part1_with(&input, &params),
+                day21::part2_with(&input, &params),
+            );
+            let input = day22(&mut rng, size);
+            let _ = (day22::part1(&input), day22::part2(&input));
+            let input = day23(&mut rng, size);
+            let _ = (day23::part1(&input), day23::part2(&input));
+            let input = day24(&mut rng, size);
+            let params = day24::Params {
+                min_x: -1e9,
+                max_x: 1e9,
+                min_y: -1e9,
+                max_y: 1e9,
+            };
+            let _ = (day24::part1_with(&input, &params), day24::part2(&input));
+            let input = day25(&mut rng, size);
+            let _ = day25::part1(&input);
+        }
+    }
+}
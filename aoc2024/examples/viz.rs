@@ -0,0 +1,39 @@
+use std::{env, fs, io, path::Path, time::Duration};
+
+use aoc2024::{day14, day15, day18};
+use aoc_common::viz::{animate, write_gif, write_pngs, Frame, Palette};
+
+fn main() -> io::Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let (day, mode) = match args.as_slice() {
+        [day, mode, ..] => (day.as_str(), mode.as_str()),
+        _ => {
+            eprintln!("usage: viz <day14|day15|day18> <term|png|gif> [output]");
+            return Ok(());
+        }
+    };
+    let input = fs::read_to_string(format!("input/2024/{day}.txt"))?;
+
+    let frames: Vec<Frame> = match day {
+        "day14" => {
+            let params = day14::Params {
+                seconds: 10_000,
+                ..day14::Params::from_env()
+            };
+            day14::visualize(&input, &params)
+        }
+        "day15" => day15::visualize(&input, true),
+        "day18" => day18::visualize(&input, &day18::Params::from_env()),
+        _ => panic!("No visualization for {day}"),
+    };
+
+    let palette = Palette::default();
+    let delay = Duration::from_millis(50);
+    let output = args.get(2).map_or(day, String::as_str);
+    match mode {
+        "term" => animate(&mut io::stdout(), &frames, &palette, delay),
+        "png" => write_pngs(&frames, Path::new(output), &palette, 4).map(|_| ()),
+        "gif" => write_gif(&frames, Path::new(output), &palette, 4, delay),
+        _ => panic!("Unknown mode: {mode}"),
+    }
+}
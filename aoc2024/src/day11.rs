@@ -54,9 +54,35 @@ fn count_stones(mut stones: HashMap<usize, usize>, blinks: usize) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::rng::Rng;
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
+
+    fn brute_force(input: &str, blinks: usize) -> usize {
+        let mut stones: Vec<usize> = input
+            .split_whitespace()
+            .map(|x| x.parse().unwrap())
+            .collect();
+        for _ in 0..blinks {
+            stones = stones
+                .into_iter()
+                .flat_map(|stone| {
+                    let digits = stone.to_string();
+                    if stone == 0 {
+                        vec![1]
+                    } else if digits.len() % 2 == 0 {
+                        let (left, right) = digits.split_at(digits.len() / 2);
+                        vec![left.parse().unwrap(), right.parse().unwrap()]
+                    } else {
+                        vec![stone * 2024]
+                    }
+                })
+                .collect();
+        }
+        stones.len()
+    }
 
     const SAMPLE: &str = indoc! {"125 17"};
 
@@ -64,4 +90,13 @@ mod tests {
     fn test_part1() {
         assert_eq!(part1(SAMPLE), 55312);
     }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(11);
+        for size in 1..4 {
+            let input = gen::day11(&mut rng, size);
+            assert_eq!(part1(&input), brute_force(&input, 25), "{input}");
+        }
+    }
 }
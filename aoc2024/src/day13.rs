@@ -131,9 +131,11 @@ pub fn part2(input: &str) -> isize {
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::rng::Rng;
     use indoc::indoc;
 
     use super::*;
+    use crate::gen;
 
     const SAMPLE: &str = indoc! {"
         Button A: X+94, Y+34
@@ -157,4 +159,33 @@ mod tests {
     fn test_part1() {
         assert_eq!(part1(SAMPLE), 480);
     }
+
+    #[test]
+    fn test_solve_against_search() {
+        let mut rng = Rng::new(13);
+        let input = gen::day13(&mut rng, 200);
+        for machine in input.split("\n\n") {
+            let nums = machine
+                .split(|c: char| !c.is_ascii_digit())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.parse::<isize>().unwrap())
+                .collect::<Vec<_>>();
+            let [ax, ay, bx, by, px, py] = nums[..] else {
+                panic!("{machine}")
+            };
+            let claw_machines = ClawMachines {
+                ax,
+                ay,
+                bx,
+                by,
+                px,
+                py,
+            };
+            assert_eq!(
+                claw_machines.calculate_2(),
+                claw_machines.calculate(),
+                "{machine}"
+            );
+        }
+    }
 }
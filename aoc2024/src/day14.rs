@@ -1,7 +1,45 @@
 use std::vec;
 
+use aoc_common::{
+    params::Overrides,
+    viz::{Frame, Recording, Visualize},
+};
 use aoc_runner_derive::aoc;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub width: isize,
+    pub height: isize,
+    pub seconds: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            width: 101,
+            height: 103,
+            seconds: 100,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params {
+        width: 11,
+        height: 7,
+        seconds: 100,
+    };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2024, "day14", "width", &mut params.width);
+        overrides.apply(2024, "day14", "height", &mut params.height);
+        overrides.apply(2024, "day14", "seconds", &mut params.seconds);
+        params
+    }
+}
+
 #[derive(Debug)]
 struct Bathroom {
     height: isize,
@@ -85,33 +123,44 @@ impl Bathroom {
             }
         });
 
-        if count >= 50 {
-            for row in 0..height {
-                for col in 0..width {
-                    if robot_bits[row][col] {
-                        print!("X");
-                    } else {
-                        print!(" ");
-                    }
-                }
-                println!();
-            }
-            return true;
+        count >= 50
+    }
+}
+
+impl Visualize for Bathroom {
+    fn frame(&self) -> Frame {
+        let mut frame = Frame::new(self.width as usize, self.height as usize);
+        for robot in &self.robots {
+            frame.set(robot.p.0 as usize, robot.p.1 as usize, '#');
         }
-        false
+        frame
     }
 }
 
+pub fn visualize(input: &str, params: &Params) -> Vec<Frame> {
+    let mut bathroom = Bathroom::from(input, params.width, params.height);
+    let mut recording = Recording::new();
+    recording.capture(&bathroom);
+    for _ in 0..params.seconds {
+        bathroom.elapse(1);
+        recording.capture(&bathroom);
+    }
+    recording.into_frames()
+}
+
+pub fn part1_with(input: &str, params: &Params) -> usize {
+    let mut bathroom = Bathroom::from(input, params.width, params.height);
+    bathroom.elapse(params.seconds);
+    bathroom.get_safety_factor()
+}
+
 #[aoc(day14, part1)]
 pub fn part1(input: &str) -> usize {
-    let mut bathroom = Bathroom::from(input, 101, 103);
-    bathroom.elapse(100);
-    bathroom.get_safety_factor()
+    part1_with(input, &Params::from_env())
 }
 
-#[aoc(day14, part2)]
-pub fn part2(input: &str) -> usize {
-    let mut bathroom = Bathroom::from(input, 101, 103);
+pub fn part2_with(input: &str, params: &Params) -> usize {
+    let mut bathroom = Bathroom::from(input, params.width, params.height);
     let mut seconds = 0;
     loop {
         bathroom.elapse(1);
@@ -123,6 +172,11 @@ pub fn part2(input: &str) -> usize {
     seconds
 }
 
+#[aoc(day14, part2)]
+pub fn part2(input: &str) -> usize {
+    part2_with(input, &Params::from_env())
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -146,9 +200,6 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        let mut bathroom = Bathroom::from(SAMPLE, 7, 11);
-        bathroom.elapse(100);
-        let answer = bathroom.get_safety_factor();
-        assert_eq!(answer, 12);
+        assert_eq!(part1_with(SAMPLE, &Params::SAMPLE), 12);
     }
 }
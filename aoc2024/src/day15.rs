@@ -2,7 +2,9 @@ use std::fmt::Display;
 use std::mem;
 use std::ops::Add;
 
+use aoc_common::viz::{Frame, Recording, Visualize};
 use aoc_runner_derive::aoc;
+
 #[derive(PartialEq, Clone, Copy, Debug)]
 enum Tile {
     Empty,
@@ -227,18 +229,39 @@ impl From<char> for Direction {
     }
 }
 
+impl Visualize for Warehouse {
+    fn frame(&self) -> Frame {
+        Frame::from_rows(
+            self.grid
+                .iter()
+                .map(|row| row.iter().map(|&tile| char::from(tile))),
+        )
+    }
+}
+
 impl Display for Warehouse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.grid {
-            for tile in row {
-                write!(f, "{}", char::from(*tile))?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+        write!(f, "{}", self.frame())
     }
 }
 
+pub fn visualize(input: &str, scaled: bool) -> Vec<Frame> {
+    let (warehouse, moves) = input.split_once("\n\n").unwrap();
+
+    let mut warehouse = Warehouse::new(warehouse);
+    if scaled {
+        warehouse.scale_width();
+    }
+
+    let mut recording = Recording::new();
+    recording.capture(&warehouse);
+    for direction in moves.lines().flat_map(|line| line.chars()) {
+        warehouse.move_robot(Direction::from(direction));
+        recording.capture(&warehouse);
+    }
+    recording.into_frames()
+}
+
 #[aoc(day15, part1)]
 pub fn part1(input: &str) -> usize {
     let (warehouse, moves) = input.split_once("\n\n").unwrap();
@@ -316,4 +339,25 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 9021);
     }
+
+    #[test]
+    fn test_visualize() {
+        let frames = visualize(SAMPLE, false);
+        assert_eq!(frames.len(), 701);
+        assert_eq!(
+            frames.last().unwrap().to_string(),
+            indoc! {"
+                ##########
+                #.O.O.OOO#
+                #........#
+                #OO......#
+                #OO@.....#
+                #O#.....O#
+                #O.....OO#
+                #O.....OO#
+                #OO....OO#
+                ##########
+            "}
+        );
+    }
 }
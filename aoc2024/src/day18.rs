@@ -4,8 +4,39 @@ use std::{
     fmt::Display,
 };
 
+use aoc_common::{
+    params::Overrides,
+    viz::{Frame, Recording, Visualize},
+};
 use aoc_runner_derive::aoc;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub size: usize,
+    pub bytes: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            size: 71,
+            bytes: 1024,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params { size: 7, bytes: 12 };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2024, "day18", "size", &mut params.size);
+        overrides.apply(2024, "day18", "bytes", &mut params.bytes);
+        params
+    }
+}
+
 struct Maze {
     gird: Vec<Vec<char>>,
     bytes: Vec<(usize, usize)>,
@@ -13,12 +44,15 @@ struct Maze {
     width: usize,
 }
 
+impl Visualize for Maze {
+    fn frame(&self) -> Frame {
+        Frame::from_rows(self.gird.iter().map(|row| row.iter().copied()))
+    }
+}
+
 impl Display for Maze {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for row in &self.gird {
-            writeln!(f, "{}", row.iter().collect::<String>())?;
-        }
-        Ok(())
+        write!(f, "{}", self.frame())
     }
 }
 
@@ -162,19 +196,37 @@ impl Maze {
     }
 }
 
+pub fn visualize(input: &str, params: &Params) -> Vec<Frame> {
+    let mut maze = Maze::from(input, params.size, params.size);
+    let mut recording = Recording::new();
+    for bytes in 0..=maze.bytes.len() {
+        maze.fill(bytes);
+        recording.capture(&maze);
+    }
+    recording.into_frames()
+}
+
+pub fn part1_with(input: &str, params: &Params) -> usize {
+    let mut maze = Maze::from(input, params.size, params.size);
+    maze.fill(params.bytes);
+    maze.find_min_step((0, 0), (params.size - 1, params.size - 1))
+}
+
 #[aoc(day18, part1)]
 pub fn part1(input: &str) -> usize {
-    let mut maze = Maze::from(input, 71, 71);
-    maze.fill(1024);
-    maze.find_min_step((0, 0), (70, 70))
+    part1_with(input, &Params::from_env())
+}
+
+pub fn part2_with(input: &str, params: &Params) -> String {
+    let mut maze = Maze::from(input, params.size, params.size);
+    let end = (params.size - 1, params.size - 1);
+    let first_byte = maze.resolve_first_byte((0, 0), end);
+    format!("{},{}", maze.bytes[first_byte].1, maze.bytes[first_byte].0)
 }
 
 #[aoc(day18, part2)]
 pub fn part2(input: &str) -> String {
-    let mut maze = Maze::from(input, 71, 71);
-    // let first_byte = maze.find_first_byte((0, 0), (70, 70));
-    let first_byte = maze.resolve_first_byte((0, 0), (70, 70));
-    format!("{},{}", maze.bytes[first_byte].1, maze.bytes[first_byte].0)
+    part2_with(input, &Params::from_env())
 }
 
 #[cfg(test)]
@@ -213,15 +265,11 @@ pub mod tests {
 
     #[test]
     fn test_part1() {
-        let mut maze = Maze::from(SAMPLE, 7, 7);
-        maze.fill(12);
-        assert_eq!(maze.find_min_step((0, 0), (6, 6)), 22);
+        assert_eq!(part1_with(SAMPLE, &Params::SAMPLE), 22);
     }
 
     #[test]
     fn test_part2() {
-        let mut maze = Maze::from(SAMPLE, 7, 7);
-        let first_byte = maze.resolve_first_byte((0, 0), (6, 6));
-        assert_eq!(first_byte, 20);
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), "6,1");
     }
 }
@@ -127,8 +127,32 @@ fn count_ways<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gen;
+    use aoc_common::rng::Rng;
     use indoc::indoc;
 
+    fn brute_force_ways(design: &str, towels: &[&str]) -> usize {
+        if design.is_empty() {
+            return 1;
+        }
+        towels
+            .iter()
+            .filter_map(|towel| design.strip_prefix(towel))
+            .map(|rest| brute_force_ways(rest, towels))
+            .sum()
+    }
+
+    fn brute_force(input: &str) -> (usize, usize) {
+        let (towels, designs) = input.split_once("\n\n").unwrap();
+        let towels = towels.split(", ").collect::<Vec<_>>();
+        designs
+            .lines()
+            .map(|design| brute_force_ways(design, &towels))
+            .fold((0, 0), |(possible, ways), n| {
+                (possible + (n > 0) as usize, ways + n)
+            })
+    }
+
     const SAMPLE: &str = indoc! {"
         r, wr, b, g, bwu, rb, gb, br
 
@@ -151,4 +175,17 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 16);
     }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(19);
+        for size in [3, 5, 8] {
+            let input = gen::day19(&mut rng, size);
+            assert_eq!(
+                (part1(&input), part2(&input)),
+                brute_force(&input),
+                "{input}"
+            );
+        }
+    }
 }
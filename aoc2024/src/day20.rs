@@ -1,9 +1,40 @@
+use aoc_common::params::Overrides;
 use aoc_runner_derive::aoc;
 
 const DIRS: [(i8, i8); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
 
 type Node = (usize, usize);
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub min_saving: usize,
+    pub max_cheat: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            min_saving: 100,
+            max_cheat: 20,
+        }
+    }
+}
+
+impl Params {
+    pub const SAMPLE: Params = Params {
+        min_saving: 50,
+        max_cheat: 20,
+    };
+
+    pub fn from_env() -> Self {
+        let overrides = Overrides::from_env();
+        let mut params = Params::default();
+        overrides.apply(2024, "day20", "min_saving", &mut params.min_saving);
+        overrides.apply(2024, "day20", "max_cheat", &mut params.max_cheat);
+        params
+    }
+}
+
 struct Maze {
     grid: Vec<Vec<char>>,
     height: usize,
@@ -88,8 +119,7 @@ impl Race {
     }
 }
 
-#[aoc(day20, part1)]
-pub fn part1(input: &str) -> usize {
+pub fn part1_with(input: &str, params: &Params) -> usize {
     let race = Race::from(input);
     let (start, end) = (race.start, race.end);
 
@@ -106,7 +136,7 @@ pub fn part1(input: &str) -> usize {
                         if race.maze.grid[next_row][next_col] == '.' {
                             let new_dist =
                                 dist_from_end[next_row][next_col] + dist_from_start[row][col] + 1;
-                            if new_dist + 100 <= normal_dist {
+                            if new_dist + params.min_saving <= normal_dist {
                                 answer += 1;
                             }
                         }
@@ -118,8 +148,12 @@ pub fn part1(input: &str) -> usize {
     answer
 }
 
-#[aoc(day20, part2)]
-pub fn part2(input: &str) -> usize {
+#[aoc(day20, part1)]
+pub fn part1(input: &str) -> usize {
+    part1_with(input, &Params::from_env())
+}
+
+pub fn part2_with(input: &str, params: &Params) -> usize {
     let race = Race::from(input);
     let (height, width) = (race.maze.height, race.maze.width);
     let (start, end) = (race.start, race.end);
@@ -128,23 +162,23 @@ pub fn part2(input: &str) -> usize {
     let dist_from_end = race.get_dist_from(end);
     let normal_dist = dist_from_start[end.0][end.1];
 
-    const M: usize = 20; // Maximum cheat duration
+    let m = params.max_cheat;
     let mut answer = 0usize;
 
     for row in 0..race.maze.height {
         for col in 0..race.maze.width {
-            if race.maze.grid[row][col] == '#' && dist_from_start[row][col] != usize::MAX {
-                for next_row in row.checked_sub(M).unwrap_or(0)..=(row + M).min(height - 1) {
-                    for next_col in col.checked_sub(M).unwrap_or(0)..=(col + M).min(width - 1) {
+            if race.maze.grid[row][col] == '.' && dist_from_start[row][col] != usize::MAX {
+                for next_row in row.checked_sub(m).unwrap_or(0)..=(row + m).min(height - 1) {
+                    for next_col in col.checked_sub(m).unwrap_or(0)..=(col + m).min(width - 1) {
                         let cheat_distance = next_row.abs_diff(row) + next_col.abs_diff(col);
-                        if cheat_distance <= M {
+                        if cheat_distance <= m {
                             if race.inside((next_row, next_col))
                                 && race.maze.grid[next_row][next_col] == '.'
                             {
                                 let new_dist = dist_from_end[next_row][next_col]
                                     + dist_from_start[row][col]
                                     + cheat_distance;
-                                if new_dist + 100 <= normal_dist {
+                                if new_dist + params.min_saving <= normal_dist {
                                     answer += 1;
                                 }
                             }
@@ -157,6 +191,11 @@ pub fn part2(input: &str) -> usize {
     answer
 }
 
+#[aoc(day20, part2)]
+pub fn part2(input: &str) -> usize {
+    part2_with(input, &Params::from_env())
+}
+
 #[cfg(test)]
 mod tests {
     use indoc::indoc;
@@ -183,11 +222,21 @@ mod tests {
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(SAMPLE), 0);
+        assert_eq!(part1_with(SAMPLE, &Params::default()), 0);
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(SAMPLE), 0);
+        assert_eq!(part2_with(SAMPLE, &Params::default()), 0);
+    }
+
+    #[test]
+    fn test_sample_params() {
+        let params = Params {
+            min_saving: 20,
+            ..Params::SAMPLE
+        };
+        assert_eq!(part1_with(SAMPLE, &params), 5);
+        assert_eq!(part2_with(SAMPLE, &Params::SAMPLE), 285);
     }
 }
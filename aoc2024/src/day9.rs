@@ -1,4 +1,7 @@
-use std::collections::VecDeque;
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+};
 
 use aoc_runner_derive::aoc;
 
@@ -45,15 +48,17 @@ pub fn part1(input: &str) -> usize {
 pub fn part2(input: &str) -> usize {
     let chars: Vec<char> = input.chars().collect();
 
+    // Free spans grouped by size, each group a min-heap of positions, so the leftmost span that
+    // fits a file is the smallest top among the groups at least as large as the file.
     let mut result: Vec<usize> = Vec::new();
-    let mut used: VecDeque<(usize, usize)> = VecDeque::new();
-    let mut avail: VecDeque<(usize, usize)> = VecDeque::new();
+    let mut used: Vec<(usize, usize)> = Vec::new();
+    let mut avail: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
     for (i, &c) in chars.iter().enumerate() {
         let digit = c.to_digit(10).unwrap() as usize;
         if i % 2 == 0 {
-            used.push_back((result.len(), digit));
-        } else {
-            avail.push_back((result.len(), digit));
+            used.push((result.len(), digit));
+        } else if digit > 0 {
+            avail[digit].push(Reverse(result.len()));
         }
         let value = if i % 2 == 0 { i / 2 } else { 0 };
         for _ in 0..digit {
@@ -61,32 +66,23 @@ pub fn part2(input: &str) -> usize {
         }
     }
 
-    let find_available =
-        |avail: &VecDeque<(usize, usize)>, used: (usize, usize)| -> Option<usize> {
-            for i in 0..avail.len() {
-                let (avail_index, avail_size) = avail[i];
-                if avail_index > used.0 {
-                    return None;
-                }
-                if avail_size >= used.1 {
-                    return Some(i);
-                }
-            }
-            return None;
-        };
-
-    for i in (0..used.len()).rev() {
-        let (used_position, used_size) = used[i];
-        if let Some(avail_index) = find_available(&avail, (used_position, used_size)) {
-            let (avail_position, avail_size) = avail[avail_index];
+    for &(used_position, used_size) in used.iter().rev() {
+        let best = (used_size.max(1)..avail.len())
+            .filter_map(|size| {
+                avail[size]
+                    .peek()
+                    .map(|&Reverse(position)| (position, size))
+            })
+            .filter(|&(position, _)| position < used_position)
+            .min();
+        if let Some((avail_position, avail_size)) = best {
+            avail[avail_size].pop();
             for j in 0..used_size {
                 result[avail_position + j] = result[used_position + j];
                 result[used_position + j] = 0;
             }
-            if avail_size == used_size {
-                avail.remove(avail_index);
-            } else {
-                avail[avail_index] = (avail_position + used_size, avail_size - used_size);
+            if avail_size > used_size {
+                avail[avail_size - used_size].push(Reverse(avail_position + used_size));
             }
         }
     }
@@ -99,7 +95,58 @@ pub fn part2(input: &str) -> usize {
 
 #[cfg(test)]
 mod tests {
+    use aoc_common::rng::Rng;
+
     use super::*;
+    use crate::gen;
+
+    fn expand(input: &str) -> Vec<Option<usize>> {
+        input
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                let file = (i % 2 == 0).then_some(i / 2);
+                std::iter::repeat_n(file, c.to_digit(10).unwrap() as usize)
+            })
+            .collect()
+    }
+
+    fn checksum(blocks: &[Option<usize>]) -> usize {
+        blocks
+            .iter()
+            .enumerate()
+            .map(|(i, id)| i * id.unwrap_or(0))
+            .sum()
+    }
+
+    fn brute_force_part1(input: &str) -> usize {
+        let mut blocks = expand(input);
+        loop {
+            let free = blocks.iter().position(Option::is_none).unwrap();
+            let last = blocks.iter().rposition(Option::is_some).unwrap();
+            if free > last {
+                break;
+            }
+            blocks.swap(free, last);
+        }
+        checksum(&blocks)
+    }
+
+    fn brute_force_part2(input: &str) -> usize {
+        let mut blocks = expand(input);
+        let max_id = blocks.iter().flatten().max().copied().unwrap();
+        for id in (0..=max_id).rev() {
+            let start = blocks.iter().position(|&b| b == Some(id)).unwrap();
+            let len = blocks.iter().filter(|&&b| b == Some(id)).count();
+            let target = (0..start).find(|&i| blocks[i..i + len].iter().all(Option::is_none));
+            if let Some(target) = target {
+                for i in 0..len {
+                    blocks.swap(target + i, start + i);
+                }
+            }
+        }
+        checksum(&blocks)
+    }
 
     const SAMPLE: &str = "2333133121414131402";
 
@@ -112,4 +159,14 @@ mod tests {
     fn test_part2() {
         assert_eq!(part2(SAMPLE), 2858);
     }
+
+    #[test]
+    fn test_against_brute_force() {
+        let mut rng = Rng::new(9);
+        for size in 1..40 {
+            let input = gen::day9(&mut rng, size);
+            assert_eq!(part1(&input), brute_force_part1(&input), "{input}");
+            assert_eq!(part2(&input), brute_force_part2(&input), "{input}");
+        }
+    }
 }
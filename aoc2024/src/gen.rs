@@ -0,0 +1,520 @@
+use aoc_common::rng::Rng;
+
+// Every day has a generator except day 17, whose part 2 searches for a program
+// that prints itself and leans on the shape of the real programs, and day 24,
+// whose part 2 expects an adder with exactly four pairs of swapped wires.
+
+fn grid(rng: &mut Rng, height: usize, width: usize, cell: impl Fn(&mut Rng) -> char) -> String {
+    (0..height)
+        .map(|_| (0..width).map(|_| cell(rng)).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A perfect maze carved over the odd cells of a square grid walled all round.
+fn carve(rng: &mut Rng, size: usize) -> Vec<Vec<char>> {
+    let side = size.max(4) / 2 * 2 + 1;
+    let mut maze = vec![vec!['#'; side]; side];
+
+    let mut stack = vec![(1, 1)];
+    maze[1][1] = '.';
+    while let Some(&(r, c)) = stack.last() {
+        let mut moves = [(0, 2), (2, 0), (0, -2), (-2, 0)]
+            .into_iter()
+            .map(|(dr, dc)| ((r as isize + dr) as usize, (c as isize + dc) as usize))
+            .filter(|&(rr, cc)| rr < side && cc < side && maze[rr][cc] == '#')
+            .collect::<Vec<_>>();
+        if moves.is_empty() {
+            stack.pop();
+            continue;
+        }
+        rng.shuffle(&mut moves);
+        let (rr, cc) = moves[0];
+        maze[(r + rr) / 2][(c + cc) / 2] = '.';
+        maze[rr][cc] = '.';
+        stack.push((rr, cc));
+    }
+    maze
+}
+
+fn join_lines(lines: impl IntoIterator<Item = String>) -> String {
+    lines.into_iter().collect::<Vec<_>>().join("\n")
+}
+
+pub fn day1(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        format!(
+            "{}   {}",
+            rng.range(10000..=99999),
+            rng.range(10000..=99999)
+        )
+    }))
+}
+
+pub fn day2(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        let len = 5 + rng.below(4);
+        let step = if rng.chance(0.5) { 1 } else { -1 };
+        let mut level = rng.range(35..=60);
+        (0..len)
+            .map(|_| {
+                level += step * rng.range(0..=4);
+                level.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }))
+}
+
+pub fn day3(rng: &mut Rng, size: usize) -> String {
+    let noise = [
+        "what()",
+        "from()",
+        "#",
+        "*",
+        "[",
+        ")",
+        "mul(4*",
+        "mul ( 2,4 )",
+        "?",
+        "do",
+        "'",
+    ];
+    (0..size)
+        .map(|_| match rng.below(6) {
+            0 | 1 => format!("mul({},{})", rng.range(0..=999), rng.range(0..=999)),
+            2 => String::from("do()"),
+            3 => String::from("don't()"),
+            _ => rng.choose(&noise).to_string(),
+        })
+        .collect()
+}
+
+pub fn day4(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| *rng.choose(&['X', 'M', 'A', 'S']))
+}
+
+pub fn day5(rng: &mut Rng, size: usize) -> String {
+    let mut pages = (10..10 + size.max(5) as i64).collect::<Vec<_>>();
+    rng.shuffle(&mut pages);
+
+    let mut rules = vec![];
+    for i in 0..pages.len() {
+        for j in i + 1..pages.len() {
+            rules.push(format!("{}|{}", pages[i], pages[j]));
+        }
+    }
+    rng.shuffle(&mut rules);
+
+    let updates = (0..size).map(|_| {
+        let mut update = pages.clone();
+        rng.shuffle(&mut update);
+        update.truncate(3 + 2 * rng.below(pages.len().min(9) / 2 - 1));
+        if rng.chance(0.5) {
+            update.sort_by_key(|page| pages.iter().position(|p| p == page));
+        }
+        update
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    });
+
+    format!("{}\n\n{}", rules.join("\n"), join_lines(updates))
+}
+
+// Obstacles are redrawn until the guard walks off the map, as part 1 expects.
+pub fn day6(rng: &mut Rng, size: usize) -> String {
+    let size = size.max(2);
+    loop {
+        let mut grid = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| if rng.chance(0.1) { '#' } else { '.' })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let start = (rng.below(size), rng.below(size));
+        grid[start.0][start.1] = '^';
+
+        let mut seen = vec![[false; 4]; size * size];
+        let (mut pos, mut dir) = (start, 0);
+        let leaves = loop {
+            if std::mem::replace(&mut seen[pos.0 * size + pos.1][dir], true) {
+                break false;
+            }
+            let (dr, dc) = [(-1, 0), (0, 1), (1, 0), (0, -1)][dir];
+            let next = (pos.0.checked_add_signed(dr), pos.1.checked_add_signed(dc));
+            let (Some(row), Some(col)) = next else {
+                break true;
+            };
+            if row >= size || col >= size {
+                break true;
+            }
+            if grid[row][col] == '#' {
+                dir = (dir + 1) % 4;
+            } else {
+                pos = (row, col);
+            }
+        };
+        if leaves {
+            return join_lines(grid.into_iter().map(|row| row.into_iter().collect()));
+        }
+    }
+}
+
+pub fn day7(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| {
+        let numbers = (0..2 + rng.below(5))
+            .map(|_| rng.range(1..=99) as u64)
+            .collect::<Vec<_>>();
+        let mut target = numbers[0];
+        for &n in &numbers[1..] {
+            target = match rng.below(3) {
+                0 => target + n,
+                1 => target * n,
+                _ => format!("{target}{n}").parse().unwrap(),
+            };
+        }
+        if rng.chance(0.3) {
+            target += 1;
+        }
+        let numbers = numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        format!("{target}: {}", numbers.join(" "))
+    }))
+}
+
+pub fn day8(rng: &mut Rng, size: usize) -> String {
+    let frequencies = ['a', 'b', 'A', 'B', '0', '1'];
+    grid(rng, size, size, |rng| {
+        if rng.chance(0.08) {
+            *rng.choose(&frequencies)
+        } else {
+            '.'
+        }
+    })
+}
+
+pub fn day9(rng: &mut Rng, size: usize) -> String {
+    (0..size * 2 + 1)
+        .map(|i| {
+            let digit = if i % 2 == 0 {
+                1 + rng.below(9)
+            } else {
+                rng.below(10)
+            };
+            char::from(b'0' + digit as u8)
+        })
+        .collect()
+}
+
+pub fn day10(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| {
+        char::from(b'0' + rng.below(10) as u8)
+    })
+}
+
+pub fn day11(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| rng.range(0..=999_999).to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn day12(rng: &mut Rng, size: usize) -> String {
+    grid(rng, size, size, |rng| *rng.choose(&['A', 'B', 'C', 'D']))
+}
+
+// The buttons never point the same way, as in the real inputs, so every prize
+// has at most one way to be reached.
+pub fn day13(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let (ax, ay) = (rng.range(10..=99), rng.range(10..=99));
+            let (mut bx, mut by) = (ax, ay);
+            while ax * by == ay * bx {
+                (bx, by) = (rng.range(10..=99), rng.range(10..=99));
+            }
+            let (a, b) = (rng.range(0..=100), rng.range(0..=100));
+            let (mut px, py) = (ax * a + bx * b, ay * a + by * b);
+            if rng.chance(0.3) {
+                px += 1;
+            }
+            format!("Button A: X+{ax}, Y+{ay}\nButton B: X+{bx}, Y+{by}\nPrize: X={px}, Y={py}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+// A picture mirrored top to bottom forms after a random number of seconds in
+// the default 101 by 103 room, so the part 2 search always ends. The rest of
+// the robots are random.
+pub fn day14(rng: &mut Rng, size: usize) -> String {
+    let (width, height) = (101, 103);
+    let seconds = rng.range(1..=500);
+    let mut robots = vec![];
+    for _ in 0..50 {
+        let (x, y) = (rng.range(0..=width - 1), rng.range(0..=height / 2 - 1));
+        robots.push((x, y));
+        robots.push((x, height - 1 - y));
+    }
+    robots.extend((0..size).map(|_| (rng.range(0..=width - 1), rng.range(0..=height - 1))));
+    join_lines(robots.into_iter().map(|(x, y)| {
+        let (vx, vy) = (rng.range(-50..=50), rng.range(-50..=50));
+        let px = (x - vx * seconds).rem_euclid(width);
+        let py = (y - vy * seconds).rem_euclid(height);
+        format!("p={px},{py} v={vx},{vy}")
+    }))
+}
+
+pub fn day15(rng: &mut Rng, size: usize) -> String {
+    let side = size.max(4);
+    let mut grid = (0..side)
+        .map(|row| {
+            (0..side)
+                .map(|col| {
+                    if row == 0 || col == 0 || row == side - 1 || col == side - 1 {
+                        '#'
+                    } else {
+                        match rng.below(10) {
+                            0 => '#',
+                            1..=3 => 'O',
+                            _ => '.',
+                        }
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    grid[1 + rng.below(side - 2)][1 + rng.below(side - 2)] = '@';
+
+    let moves = (0..1 + size / 2).map(|_| {
+        (0..1 + rng.below(70))
+            .map(|_| *rng.choose(&['^', 'v', '<', '>']))
+            .collect::<String>()
+    });
+    format!(
+        "{}\n\n{}",
+        join_lines(grid.into_iter().map(|row| row.into_iter().collect())),
+        join_lines(moves)
+    )
+}
+
+// A maze with some extra walls knocked out, so several routes of different
+// costs lead from the bottom-left start to the top-right end.
+pub fn day16(rng: &mut Rng, size: usize) -> String {
+    let mut maze = carve(rng, size);
+    let side = maze.len();
+    for _ in 0..side {
+        let (r, c) = (1 + rng.below(side - 2), 1 + rng.below(side - 2));
+        if (r + c) % 2 == 1 {
+            maze[r][c] = '.';
+        }
+    }
+    maze[side - 2][1] = 'S';
+    maze[1][side - 2] = 'E';
+    join_lines(maze.iter().map(|row| row.iter().collect()))
+}
+
+pub fn day18(rng: &mut Rng, size: usize) -> String {
+    let mut bytes = (0..size)
+        .flat_map(|row| (0..size).map(move |col| (row, col)))
+        .filter(|&cell| cell != (0, 0) && cell != (size - 1, size - 1))
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut bytes);
+    join_lines(bytes.iter().map(|(row, col)| format!("{col},{row}")))
+}
+
+pub fn day19(rng: &mut Rng, size: usize) -> String {
+    let colors = ['w', 'u', 'b', 'r', 'g'];
+    let mut towels = (0..size)
+        .map(|_| {
+            (0..1 + rng.below(3))
+                .map(|_| *rng.choose(&colors))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>();
+    towels.sort();
+    towels.dedup();
+
+    let designs = (0..size).map(|_| {
+        (0..4 + rng.below(8))
+            .map(|_| *rng.choose(&colors))
+            .collect::<String>()
+    });
+
+    format!("{}\n\n{}", towels.join(", "), join_lines(designs))
+}
+
+// The solver expects a single track without branches, so this keeps only the
+// route between two corners of a maze and walls off everything else.
+pub fn day20(rng: &mut Rng, size: usize) -> String {
+    let maze = carve(rng, size);
+    let side = maze.len();
+    let (start, end) = ((side - 2, 1), (1, side - 2));
+
+    let mut prev = vec![vec![None; side]; side];
+    let mut stack = vec![start];
+    while let Some((r, c)) = stack.pop() {
+        for (dr, dc) in [(0, 1), (1, 0), (0, -1), (-1, 0)] {
+            let (rr, cc) = ((r as isize + dr) as usize, (c as isize + dc) as usize);
+            if maze[rr][cc] == '.' && prev[rr][cc].is_none() && (rr, cc) != start {
+                prev[rr][cc] = Some((r, c));
+                stack.push((rr, cc));
+            }
+        }
+    }
+
+    let mut track = vec![vec!['#'; side]; side];
+    let mut pos = end;
+    while let Some(next) = prev[pos.0][pos.1] {
+        track[pos.0][pos.1] = '.';
+        pos = next;
+    }
+    track[start.0][start.1] = 'S';
+    track[end.0][end.1] = 'E';
+    join_lines(track.iter().map(|row| row.iter().collect()))
+}
+
+pub fn day21(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| format!("{:03}A", rng.below(1000))))
+}
+
+pub fn day22(rng: &mut Rng, size: usize) -> String {
+    join_lines((0..size).map(|_| rng.range(1..=16_777_215).to_string()))
+}
+
+// The part 2 search relies on every computer having the same number of
+// connections, as in the real inputs, so this builds a shuffled circulant graph.
+pub fn day23(rng: &mut Rng, size: usize) -> String {
+    let mut names = (0..26 * 26)
+        .map(|i| String::from_utf8(vec![b'a' + (i / 26) as u8, b'a' + (i % 26) as u8]).unwrap())
+        .collect::<Vec<_>>();
+    rng.shuffle(&mut names);
+
+    let size = size.clamp(5, names.len());
+    let mut edges = vec![];
+    for i in 0..size {
+        for j in 1..=2 {
+            edges.push(format!("{}-{}", names[i], names[(i + j) % size]));
+        }
+    }
+    rng.shuffle(&mut edges);
+    join_lines(edges)
+}
+
+pub fn day25(rng: &mut Rng, size: usize) -> String {
+    (0..size)
+        .map(|i| {
+            let lock = i % 2 == 0;
+            let heights = (0..5).map(|_| rng.below(6)).collect::<Vec<_>>();
+            (0..7)
+                .map(|row| {
+                    heights
+                        .iter()
+                        .map(|&h| {
+                            let filled = if lock { row <= h } else { row >= 6 - h };
+                            if filled {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use aoc_common::stress::within_budget;
+
+    use super::*;
+    use crate::*;
+
+    // Only checks that every solver accepts what the generators make. The
+    // answers are compared against reference solutions in the days' own tests.
+    #[test]
+    fn test_generated_inputs_smoke() {
+        let mut rng = Rng::new(2024);
+        for size in [5, 20] {
+            let input = day1(&mut rng, size);
+            let _ = (day1::part1(&input), day1::part2(&input));
+            let input = day2(&mut rng, size);
+            let _ = (day2::part1(&input), day2::part2(&input));
+            let input = day3(&mut rng, size);
+            let _ = (day3::part1(&input), day3::part2(&input));
+            let input = day4(&mut rng, size);
+            let _ = (day4::part1(&input), day4::part2(&input));
+            let input = day5(&mut rng, size);
+            let _ = (day5::part1(&input), day5::part2(&input));
+            let input = day6(&mut rng, size);
+            let _ = (day6::part1(&input), day6::part2(&input));
+            let input = day7(&mut rng, size);
+            let _ = (day7::part1(&input), day7::part2(&input));
+            let input = day8(&mut rng, size);
+            let _ = (day8::part1(&input), day8::part2(&input));
+            let input = day9(&mut rng, size);
+            let _ = (day9::part1(&input), day9::part2(&input));
+            let input = day10(&mut rng, size);
+            let _ = (day10::part1(&input), day10::part2(&input));
+            let input = day11(&mut rng, size);
+            let _ = (day11::part1(&input), day11::part2(&input));
+            let input = day12(&mut rng, size);
+            let _ = (day12::part1(&input), day12::part2(&input));
+            let input = day13(&mut rng, size);
+            let _ = (day13::part1(&input), day13::part2(&input));
+            let input = day14(&mut rng, size);
+            let params = day14::Params::default();
+            let _ = (
+                day14::part1_with(&input, &params),
+                day14::part2_with(&input, &params),
+            );
+            let input = day15(&mut rng, size);
+            let _ = (day15::part1(&input), day15::part2(&input));
+            let input = day16(&mut rng, size);
+            let _ = (day16::part1(&input), day16::part2(&input));
+            let input = day18(&mut rng, size);
+            let params = day18::Params { size, bytes: size };
+            let _ = (
+                day18::part1_with(&input, &params),
+                day18::part2_with(&input, &params),
+            );
+            let input = day19(&mut rng, size);
+            let _ = (day19::part1(&input), day19::part2(&input));
+            let input = day20(&mut rng, size);
+            let params = day20::Params {
+                min_saving: 2,
+                max_cheat: 20,
+            };
+            let _ = (
+                day20::part1_with(&input, &params),
+                day20::part2_with(&input, &params),
+            );
+            let input = day21(&mut rng, size);
+            let _ = (day21::part1(&input), day21::part2(&input));
+            let input = day22(&mut rng, size);
+            let _ = (day22::part1(&input), day22::part2(&input));
+            let input = day23(&mut rng, size);
+            let _ = (day23::part1(&input), day23::part2(&input));
+            let input = day25(&mut rng, size);
+            let _ = day25::part1(&input);
+        }
+    }
+
+    // The sample-sized inputs above can't show how a solver scales, so this
+    // grows the input well past the real one.
+    #[test]
+    fn test_scaling() {
+        let mut rng = Rng::new(2024);
+        let inputs = [10_000, 40_000, 160_000].map(|size| (size, day9(&mut rng, size)));
+        within_budget(inputs.clone(), Duration::from_secs(2), day9::part1);
+        within_budget(inputs, Duration::from_secs(2), day9::part2);
+    }
+}
@@ -23,5 +23,6 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod gen;
 
 aoc_runner_derive::aoc_lib! { year = 2024 }
@@ -0,0 +1,137 @@
+use std::fmt::Debug;
+
+// Trailing zero words are always trimmed, so equal sets compare and hash
+// equally regardless of how they were built.
+#[derive(Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        BitSet::default()
+    }
+
+    pub fn contains(&self, bit: usize) -> bool {
+        self.words
+            .get(bit / 64)
+            .is_some_and(|word| word & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn insert(&mut self, bit: usize) -> bool {
+        if self.words.len() <= bit / 64 {
+            self.words.resize(bit / 64 + 1, 0);
+        }
+        let word = &mut self.words[bit / 64];
+        let added = *word & (1 << (bit % 64)) == 0;
+        *word |= 1 << (bit % 64);
+        added
+    }
+
+    pub fn remove(&mut self, bit: usize) -> bool {
+        let Some(word) = self.words.get_mut(bit / 64) else {
+            return false;
+        };
+        let removed = *word & (1 << (bit % 64)) != 0;
+        *word &= !(1 << (bit % 64));
+        self.trim();
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn union(&self, other: &BitSet) -> BitSet {
+        let (long, short) = if self.words.len() >= other.words.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut words = long.words.clone();
+        for (word, other) in words.iter_mut().zip(&short.words) {
+            *word |= other;
+        }
+        BitSet { words }
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        if self.words.len() < other.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64)
+                .filter(move |bit| word & (1 << bit) != 0)
+                .map(move |bit| i * 64 + bit)
+        })
+    }
+
+    fn trim(&mut self) {
+        while self.words.last() == Some(&0) {
+            self.words.pop();
+        }
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<T: IntoIterator<Item = usize>>(iter: T) -> Self {
+        let mut set = BitSet::new();
+        for bit in iter {
+            set.insert(bit);
+        }
+        set
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_past_one_word() {
+        let mut a = BitSet::from_iter([3, 64, 130]);
+        let b = BitSet::from_iter([4, 65]);
+        assert!(a.contains(130) && !a.contains(129));
+        assert!(a.is_disjoint(&b));
+        assert_eq!(a.union(&b).iter().collect::<Vec<_>>(), [3, 4, 64, 65, 130]);
+        assert_eq!(a.union(&b).len(), 5);
+        let mut c = b.clone();
+        c.union_with(&a);
+        assert_eq!(c, a.union(&b));
+
+        assert!(a.remove(130));
+        assert_eq!(a, BitSet::from_iter([64, 3]));
+        assert!(a.is_subset(&BitSet::from_iter([3, 64, 200])));
+        assert!(!a.is_subset(&b));
+    }
+}
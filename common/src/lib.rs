@@ -0,0 +1,7 @@
+pub mod bitset;
+pub mod math;
+pub mod ocr;
+pub mod params;
+pub mod rng;
+pub mod stress;
+pub mod viz;
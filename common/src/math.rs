@@ -0,0 +1,53 @@
+pub fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: u64, b: u64) -> u64 {
+    a / gcd(a, b) * b
+}
+
+// Returns (g, x, y) with a * x + b * y = g.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
+}
+
+// Combines congruences `t = residue (mod modulus)` whose moduli need not be
+// coprime. Returns the combined (residue, modulus), or None when they conflict.
+pub fn crt(congruences: impl IntoIterator<Item = (u64, u64)>) -> Option<(u64, u64)> {
+    let mut result = (0i128, 1i128);
+    for (residue, modulus) in congruences {
+        let (r1, m1) = result;
+        let (r2, m2) = (residue as i128 % modulus as i128, modulus as i128);
+        let (g, x, _) = extended_gcd(m1, m2);
+        if (r2 - r1) % g != 0 {
+            return None;
+        }
+        let m = m1 / g * m2;
+        let k = ((r2 - r1) / g * x).rem_euclid(m2 / g);
+        result = ((r1 + m1 * k).rem_euclid(m), m);
+    }
+    Some((result.0 as u64, result.1 as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crt() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        assert_eq!(crt([(1, 4), (3, 6)]), Some((9, 12)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([]), Some((0, 1)));
+        assert_eq!(lcm(4, 6), 12);
+    }
+}
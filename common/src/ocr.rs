@@ -0,0 +1,141 @@
+struct Font {
+    letters: &'static str,
+    width: usize,
+    spacing: usize,
+    sheet: &'static [&'static str],
+}
+
+impl Font {
+    fn glyph(&self, index: usize) -> impl Iterator<Item = &'static [u8]> + '_ {
+        let start = index * self.spacing;
+        self.sheet
+            .iter()
+            .map(move |row| &row.as_bytes()[start..start + self.width])
+    }
+}
+
+const SMALL: Font = Font {
+    letters: "ABCEFGHIJKLOPRSUZ",
+    width: 4,
+    spacing: 5,
+    sheet: &[
+        ".##..###...##..####.####..##..#..#..###...##.#..#.#.....##..###..###...###.#..#.####",
+        "#..#.#..#.#..#.#....#....#..#.#..#...#.....#.#.#..#....#..#.#..#.#..#.#....#..#....#",
+        "#..#.###..#....###..###..#....####...#.....#.##...#....#..#.#..#.#..#.#....#..#...#.",
+        "####.#..#.#....#....#....#.##.#..#...#.....#.#.#..#....#..#.###..###...##..#..#..#..",
+        "#..#.#..#.#..#.#....#....#..#.#..#...#..#..#.#.#..#....#..#.#....#.#.....#.#..#.#...",
+        "#..#.###...##..####.#.....###.#..#..###..##..#..#.####..##..#....#..#.###...##..####",
+    ],
+};
+
+const LARGE: Font = Font {
+    letters: "ABCEFGHJKLNPRXZ",
+    width: 6,
+    spacing: 8,
+    sheet: &[
+        "..##....#####....####...######..######...####...#....#.....###..#....#..#.......#....#..#####...#####...#....#..######",
+        ".#..#...#....#..#....#..#.......#.......#....#..#....#......#...#...#...#.......##...#..#....#..#....#..#....#.......#",
+        "#....#..#....#..#.......#.......#.......#.......#....#......#...#..#....#.......##...#..#....#..#....#...#..#........#",
+        "#....#..#....#..#.......#.......#.......#.......#....#......#...#.#.....#.......#.#..#..#....#..#....#...#..#.......#.",
+        "#....#..#####...#.......#####...#####...#.......######......#...##......#.......#.#..#..#####...#####.....##.......#..",
+        "######..#....#..#.......#.......#.......#..###..#....#......#...##......#.......#..#.#..#.......#..#......##......#...",
+        "#....#..#....#..#.......#.......#.......#....#..#....#......#...#.#.....#.......#..#.#..#.......#...#....#..#....#....",
+        "#....#..#....#..#.......#.......#.......#....#..#....#..#...#...#..#....#.......#...##..#.......#...#....#..#...#.....",
+        "#....#..#....#..#....#..#.......#.......#...##..#....#..#...#...#...#...#.......#...##..#.......#....#..#....#..#.....",
+        "#....#..#####....####...######..#........###.#..#....#...###....#....#..######..#....#..#.......#....#..#....#..######",
+    ],
+};
+
+fn glyph_matches(
+    grid: &[Vec<bool>],
+    left: usize,
+    glyph: impl Iterator<Item = &'static [u8]>,
+) -> bool {
+    glyph.zip(grid).all(|(pattern, row)| {
+        pattern
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| row.get(left + i).copied().unwrap_or(false) == (b == b'#'))
+    })
+}
+
+pub fn recognize(grid: &[Vec<bool>]) -> Option<String> {
+    let top = grid.iter().position(|row| row.contains(&true))?;
+    let bottom = grid.iter().rposition(|row| row.contains(&true))?;
+    let rows = &grid[top..=bottom];
+    let font = [SMALL, LARGE]
+        .into_iter()
+        .find(|font| font.sheet.len() == rows.len())?;
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let ink = |col: usize| rows.iter().any(|row| row.get(col) == Some(&true));
+
+    let mut text = String::new();
+    let mut col = 0;
+    while col < width {
+        if !ink(col) {
+            col += 1;
+            continue;
+        }
+        // A glyph may start with blank columns (e.g. `I`), so line its first
+        // inked column up with the current one.
+        let (letter, left) = font.letters.chars().enumerate().find_map(|(i, letter)| {
+            let lead = (0..font.width).find(|&c| font.glyph(i).any(|row| row[c] == b'#'))?;
+            let left = col.checked_sub(lead)?;
+            glyph_matches(rows, left, font.glyph(i)).then_some((letter, left))
+        })?;
+        text.push(letter);
+        col = left + font.width;
+    }
+    Some(text)
+}
+
+pub fn recognize_str(image: &str) -> Option<String> {
+    let grid = image
+        .lines()
+        .map(|line| line.chars().map(|c| c == '#').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    recognize(&grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_font() {
+        let image = [
+            "###..#..#.###....##.###..###..#.....##..",
+            "#..#.#.#..#..#....#.#..#.#..#.#....#..#.",
+            "#..#.##...#..#....#.###..#..#.#....#..#.",
+            "###..#.#..###.....#.#..#.###..#....####.",
+            "#.#..#.#..#....#..#.#..#.#.#..#....#..#.",
+            "#..#.#..#.#.....##..###..#..#.####.#..#.",
+        ]
+        .join("\n");
+        assert_eq!(recognize_str(&image).as_deref(), Some("RKPJBRLA"));
+    }
+
+    #[test]
+    fn test_large_font() {
+        let image = [
+            "..........................",
+            "...#....#..######..#####..",
+            "...#....#..#.......#....#.",
+            "...#....#..#.......#....#.",
+            "...#....#..#.......#....#.",
+            "...######..#####...#####..",
+            "...#....#..#.......#..#...",
+            "...#....#..#.......#...#..",
+            "...#....#..#.......#...#..",
+            "...#....#..#.......#....#.",
+            "...#....#..######..#....#.",
+        ]
+        .join("\n");
+        assert_eq!(recognize_str(&image).as_deref(), Some("HER"));
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        assert_eq!(recognize_str("#.#\n.#.\n#.#"), None);
+    }
+}
@@ -0,0 +1,100 @@
+use std::{collections::HashMap, env, fmt::Debug, str::FromStr};
+
+pub const ENV_VAR: &str = "AOC_PARAMS";
+
+// Overrides are `year.day.name=value` pairs, e.g. `2024.day14.width=11`. They
+// are read from the `AOC_PARAMS` variable (comma or whitespace separated) and
+// from `--param year.day.name=value` command line arguments, with the command
+// line taking precedence. Other arguments are left alone, so test harness
+// filters never turn into overrides.
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    values: HashMap<String, String>,
+}
+
+impl Overrides {
+    pub fn parse<'a>(specs: impl IntoIterator<Item = &'a str>) -> Self {
+        let values = specs
+            .into_iter()
+            .flat_map(|spec| spec.split(|c: char| c == ',' || c.is_whitespace()))
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Overrides { values }
+    }
+
+    pub fn from_env() -> Self {
+        let from_var = env::var(ENV_VAR).unwrap_or_default();
+        let args = param_args(env::args().skip(1));
+        Overrides::parse(std::iter::once(from_var.as_str()).chain(args.iter().map(String::as_str)))
+    }
+
+    pub fn get<T>(&self, year: u32, day: &str, name: &str) -> Option<T>
+    where
+        T: FromStr,
+        T::Err: Debug,
+    {
+        let key = format!("{year}.{day}.{name}");
+        self.values.get(&key).map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|e| panic!("Invalid value for {key}: {value} ({e:?})"))
+        })
+    }
+
+    pub fn apply<T>(&self, year: u32, day: &str, name: &str, target: &mut T)
+    where
+        T: FromStr,
+        T::Err: Debug,
+    {
+        if let Some(value) = self.get(year, day, name) {
+            *target = value;
+        }
+    }
+}
+
+// The values following each `--param` flag.
+fn param_args(args: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut args = args.into_iter();
+    let mut params = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--param" {
+            params.extend(args.next());
+        }
+    }
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let overrides = Overrides::parse([
+            "2024.day14.width=11, 2024.day14.height=7",
+            "2022.day15.row=10",
+        ]);
+        assert_eq!(overrides.get::<usize>(2024, "day14", "width"), Some(11));
+        assert_eq!(overrides.get::<usize>(2024, "day14", "height"), Some(7));
+        assert_eq!(overrides.get::<i64>(2022, "day15", "row"), Some(10));
+        assert_eq!(overrides.get::<i64>(2022, "day15", "max"), None);
+        assert_eq!(overrides.get::<usize>(2023, "day14", "width"), None);
+
+        let mut max = 4_000_000;
+        overrides.apply(2022, "day15", "max", &mut max);
+        assert_eq!(max, 4_000_000);
+    }
+
+    #[test]
+    fn test_param_args() {
+        let args = [
+            "day14.width=3",
+            "--param",
+            "2024.day14.width=11",
+            "--nocapture",
+            "--param",
+        ];
+        assert_eq!(param_args(args.map(String::from)), ["2024.day14.width=11"]);
+    }
+}
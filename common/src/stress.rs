@@ -0,0 +1,43 @@
+use std::{
+    sync::mpsc::{self, RecvTimeoutError},
+    thread,
+    time::Duration,
+};
+
+// Solves each input on a worker thread and panics as soon as one takes longer
+// than `budget`, so a solver that scales badly fails the test instead of
+// hanging it. Inputs are `(size, input)` pairs, smallest first.
+pub fn within_budget<T: 'static>(
+    inputs: impl IntoIterator<Item = (usize, String)>,
+    budget: Duration,
+    solve: fn(&str) -> T,
+) {
+    for (size, input) in inputs {
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            solve(&input);
+            let _ = tx.send(());
+        });
+        match rx.recv_timeout(budget) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => panic!("size {size} took longer than {budget:?}"),
+            Err(RecvTimeoutError::Disconnected) => panic!("size {size} panicked"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "size 2 took longer than")]
+    fn test_within_budget() {
+        let slow = |input: &str| thread::sleep(Duration::from_millis(100 * input.len() as u64));
+        within_budget(
+            [(1, String::new()), (2, String::from("xx"))],
+            Duration::from_millis(50),
+            slow,
+        );
+    }
+}
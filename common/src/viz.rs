@@ -0,0 +1,111 @@
+use std::fmt::Display;
+
+#[cfg(feature = "viz")]
+mod render;
+
+#[cfg(feature = "viz")]
+pub use render::{animate, write_gif, write_pngs, Palette};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<char>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec!['.'; width * height],
+        }
+    }
+
+    pub fn from_rows<R, C>(rows: R) -> Self
+    where
+        R: IntoIterator<Item = C>,
+        C: IntoIterator<Item = char>,
+    {
+        let rows = rows
+            .into_iter()
+            .map(|row| row.into_iter().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let mut frame = Frame::new(width, rows.len());
+        for (y, row) in rows.into_iter().enumerate() {
+            for (x, c) in row.into_iter().enumerate() {
+                frame.set(x, y, c);
+            }
+        }
+        frame
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> char {
+        self.cells[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, c: char) {
+        self.cells[y * self.width + x] = c;
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[char]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.rows() {
+            writeln!(f, "{}", row.iter().collect::<String>())?;
+        }
+        Ok(())
+    }
+}
+
+pub trait Visualize {
+    fn frame(&self) -> Frame;
+}
+
+#[derive(Debug, Default)]
+pub struct Recording {
+    frames: Vec<Frame>,
+}
+
+impl Recording {
+    pub fn new() -> Self {
+        Recording::default()
+    }
+
+    pub fn capture<V: Visualize>(&mut self, state: &V) {
+        self.frames.push(state.frame());
+    }
+
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    pub fn into_frames(self) -> Vec<Frame> {
+        self.frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let frame = Frame::from_rows(["#..", ".#", "..#"].map(str::chars));
+        assert_eq!((frame.width(), frame.height()), (3, 3));
+        assert_eq!(frame.to_string(), "#..\n.#.\n..#\n");
+    }
+}
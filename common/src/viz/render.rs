@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use super::Frame;
+
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: HashMap<char, [u8; 3]>,
+    background: [u8; 3],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        let background = [15, 15, 35];
+        let colors = [
+            ('.', background),
+            (' ', background),
+            ('#', [200, 200, 200]),
+            ('@', [255, 215, 0]),
+            ('O', [160, 110, 60]),
+            ('[', [160, 110, 60]),
+            (']', [160, 110, 60]),
+            ('|', [90, 90, 90]),
+            ('-', [90, 90, 90]),
+            ('+', [90, 90, 90]),
+        ];
+        Palette {
+            colors: colors.into_iter().collect(),
+            background,
+        }
+    }
+}
+
+impl Palette {
+    pub fn with(mut self, c: char, color: [u8; 3]) -> Self {
+        self.colors.insert(c, color);
+        self
+    }
+
+    pub fn color(&self, c: char) -> [u8; 3] {
+        self.colors.get(&c).copied().unwrap_or_else(|| {
+            // Spread unknown glyphs over the color space so they stay distinguishable.
+            let hash = (c as u32).wrapping_mul(2654435761);
+            [
+                (hash >> 24) as u8 | 0x40,
+                (hash >> 16) as u8 | 0x40,
+                (hash >> 8) as u8 | 0x40,
+            ]
+        })
+    }
+}
+
+pub fn animate<W: Write>(
+    out: &mut W,
+    frames: &[Frame],
+    palette: &Palette,
+    delay: Duration,
+) -> io::Result<()> {
+    for frame in frames {
+        write!(out, "\x1b[H\x1b[2J")?;
+        for row in frame.rows() {
+            for &c in row {
+                let [r, g, b] = palette.color(c);
+                write!(out, "\x1b[38;2;{r};{g};{b}m{c}")?;
+            }
+            writeln!(out, "\x1b[0m")?;
+        }
+        out.flush()?;
+        thread::sleep(delay);
+    }
+    Ok(())
+}
+
+// Frames of a growing simulation can differ in size, so every image is drawn on
+// a canvas large enough for the biggest frame.
+fn canvas_size(frames: &[Frame]) -> (usize, usize) {
+    frames.iter().fold((0, 0), |(width, height), frame| {
+        (width.max(frame.width()), height.max(frame.height()))
+    })
+}
+
+fn rasterize(
+    frame: &Frame,
+    (width, height): (usize, usize),
+    scale: usize,
+    mut pixel: impl FnMut(Option<char>),
+) {
+    for y in 0..height * scale {
+        for x in 0..width * scale {
+            let (col, row) = (x / scale, y / scale);
+            if col < frame.width() && row < frame.height() {
+                pixel(Some(frame.get(col, row)));
+            } else {
+                pixel(None);
+            }
+        }
+    }
+}
+
+pub fn write_pngs(
+    frames: &[Frame],
+    dir: &Path,
+    palette: &Palette,
+    scale: usize,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let size = canvas_size(frames);
+
+    frames
+        .iter()
+        .enumerate()
+        .map(|(i, frame)| {
+            let path = dir.join(format!("frame_{i:05}.png"));
+            let mut data = Vec::with_capacity(size.0 * size.1 * scale * scale * 3);
+            rasterize(frame, size, scale, |c| {
+                data.extend(c.map_or(palette.background, |c| palette.color(c)));
+            });
+
+            let file = BufWriter::new(File::create(&path)?);
+            let mut encoder =
+                png::Encoder::new(file, (size.0 * scale) as u32, (size.1 * scale) as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder
+                .write_header()
+                .and_then(|mut writer| writer.write_image_data(&data))
+                .map_err(io::Error::other)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+pub fn write_gif(
+    frames: &[Frame],
+    path: &Path,
+    palette: &Palette,
+    scale: usize,
+    delay: Duration,
+) -> io::Result<()> {
+    let size = canvas_size(frames);
+    let side = |cells: usize| {
+        cells
+            .checked_mul(scale)
+            .and_then(|pixels| u16::try_from(pixels).ok())
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "a gif is at most 65535 pixels wide and tall",
+                )
+            })
+    };
+    let (width, height) = (side(size.0)?, side(size.1)?);
+
+    let mut glyphs = vec![None];
+    for frame in frames {
+        for row in frame.rows() {
+            for &c in row {
+                if !glyphs.contains(&Some(c)) {
+                    glyphs.push(Some(c));
+                }
+            }
+        }
+    }
+    if glyphs.len() > 256 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a gif palette holds at most 256 colors",
+        ));
+    }
+    let global_palette = glyphs
+        .iter()
+        .flat_map(|c| c.map_or(palette.background, |c| palette.color(c)))
+        .collect::<Vec<_>>();
+
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder =
+        gif::Encoder::new(file, width, height, &global_palette).map_err(io::Error::other)?;
+    encoder
+        .set_repeat(gif::Repeat::Infinite)
+        .map_err(io::Error::other)?;
+
+    for frame in frames {
+        let mut pixels = Vec::with_capacity(width as usize * height as usize);
+        rasterize(frame, size, scale, |c| {
+            pixels.push(glyphs.iter().position(|&g| g == c).unwrap() as u8);
+        });
+        let mut image = gif::Frame::from_indexed_pixels(width, height, pixels, None);
+        image.delay = (delay.as_millis() / 10).min(u16::MAX.into()) as u16;
+        encoder.write_frame(&image).map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_images() {
+        let frames = [
+            Frame::from_rows(["#.", ".#"].map(str::chars)),
+            Frame::from_rows(["@.#"].map(str::chars)),
+        ];
+        let dir = std::env::temp_dir().join(format!("aoc-viz-{}", std::process::id()));
+        let palette = Palette::default();
+
+        let pngs = write_pngs(&frames, &dir, &palette, 2).unwrap();
+        assert_eq!(pngs.len(), 2);
+        assert!(pngs.iter().all(|path| path.exists()));
+
+        let gif = dir.join("frames.gif");
+        write_gif(&frames, &gif, &palette, 2, Duration::from_millis(100)).unwrap();
+        assert!(fs::metadata(&gif).unwrap().len() > 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_gif_too_large() {
+        let frames = [Frame::from_rows(
+            [".".repeat(40_000)].iter().map(|row| row.chars()),
+        )];
+        let gif = std::env::temp_dir().join(format!("aoc-viz-large-{}.gif", std::process::id()));
+        let err = write_gif(&frames, &gif, &Palette::default(), 2, Duration::ZERO).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(!gif.exists());
+    }
+}